@@ -7,7 +7,7 @@ fn main() {
     }
     assert_eq!(st, [10, 9, 8, 7, 6]);
 
-    let a = vec![1, 2, 3, 4];
+    let a = [1, 2, 3, 4];
     let st = SlidingWindow::from(&a[..]);
     assert!(st == &a[..]);
 
@@ -46,6 +46,7 @@ fn main() {
     assert_eq!(b, a);
 
     let mut idx = 0;
+    #[allow(clippy::explicit_counter_loop)]
     for el in &st {
         // calling into_iter() for &st, non consuming
         // (it consumes the reference) O(n)
@@ -55,6 +56,7 @@ fn main() {
     assert_eq!(5, st.capacity());
 
     let mut idx = 0;
+    #[allow(clippy::explicit_counter_loop)]
     for el in st {
         // implicitly calling into_iter() and consuming st O(n)
         assert_eq!(el, a[idx]);
@@ -0,0 +1,157 @@
+//! Adaptive FIR filters, whose regressor is a [`SlidingWindow`] of the past
+//! inputs, aligned with the weights, newest first.
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::{AdaptiveFilter, Update};
+    use crate::test_input as input;
+    const PLANT: [f64; 4] = [0.5, -0.3, 0.2, 0.1];
+    fn identify(update: Update) -> AdaptiveFilter {
+        let mut plant = crate::SlidingWindow::new(4, 0.0);
+        let mut af = AdaptiveFilter::new(4, update);
+        for t in 0..5000 {
+            let x = input(t);
+            plant.push(x);
+            let d: f64 = plant.iter().zip(PLANT).map(|(x, h)| x * h).sum();
+            af.step(x, d);
+        }
+        af
+    }
+    #[test]
+    fn lms() {
+        let af = identify(Update::Lms { mu: 0.05 });
+        assert!(af
+            .weights()
+            .iter()
+            .zip(PLANT)
+            .all(|(w, h)| (w - h).abs() < 1e-3));
+        let (y, e) = af.clone().step(0.0, 0.0);
+        assert_eq!(y + e, 0.0);
+    }
+    #[test]
+    fn nlms() {
+        let af = identify(Update::Nlms { mu: 0.5, eps: 1e-6 });
+        assert!(af
+            .weights()
+            .iter()
+            .zip(PLANT)
+            .all(|(w, h)| (w - h).abs() < 1e-6));
+    }
+    #[test]
+    fn leaky() {
+        let af = identify(Update::Leaky {
+            mu: 0.05,
+            leak: 0.01,
+        });
+        // the leak biases the weights towards zero
+        assert!(af
+            .weights()
+            .iter()
+            .zip(PLANT)
+            .all(|(w, h)| (w - h).abs() < 0.05));
+        assert!(af.weights()[0] < PLANT[0]);
+    }
+    #[test]
+    fn output_is_aligned() {
+        let mut af = AdaptiveFilter::with_weights(vec![1.0, 10.0, 100.0], Update::Lms { mu: 0.0 });
+        af.step(1.0, 0.0);
+        af.step(2.0, 0.0);
+        let (y, _) = af.step(3.0, 0.0);
+        assert_eq!(y, 3.0 + 20.0 + 100.0);
+        assert_eq!(af.output(), y);
+        assert_eq!(*af.regressor(), [3.0, 2.0, 1.0]);
+    }
+}
+
+/// How the weights adapt to the error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Update {
+    /// Least mean squares, with step size `mu`, which has to be small compared
+    /// to the inverse of the power of the input times the amount of taps.
+    Lms {
+        /// Step size.
+        mu: f64,
+    },
+    /// Normalized LMS, where the step is divided by the power of the
+    /// regressor, so it's stable for `mu` in `(0, 2)`.
+    Nlms {
+        /// Step size.
+        mu: f64,
+        /// Regularization, to avoid dividing by zero.
+        eps: f64,
+    },
+    /// LMS where the weights also decay towards zero, which keeps them bounded
+    /// with poorly exciting inputs.
+    Leaky {
+        /// Step size.
+        mu: f64,
+        /// Decay per step, relative to `mu`.
+        leak: f64,
+    },
+}
+
+/// An adaptive FIR filter.
+#[derive(Debug, Clone)]
+pub struct AdaptiveFilter {
+    regressor: SlidingWindow<f64>,
+    weights: Vec<f64>,
+    update: Update,
+}
+
+impl AdaptiveFilter {
+    /// Creates a filter of `taps` weights, all of them zero, like the past
+    /// inputs.
+    pub fn new(taps: usize, update: Update) -> AdaptiveFilter {
+        let regressor = SlidingWindow::new(taps, 0.0);
+        AdaptiveFilter {
+            weights: vec![0.0; regressor.capacity()],
+            regressor,
+            update,
+        }
+    }
+    /// Creates a filter with initial `weights`, where index `0` multiplies the
+    /// newest input. The past inputs start as zero.
+    pub fn with_weights(weights: Vec<f64>, update: Update) -> AdaptiveFilter {
+        let mut af = AdaptiveFilter::new(weights.len(), update);
+        af.weights.iter_mut().zip(weights).for_each(|(w, v)| *w = v);
+        af
+    }
+    /// Returns the output of the filter for the current regressor.
+    pub fn output(&self) -> f64 {
+        self.regressor
+            .iter()
+            .zip(&self.weights)
+            .map(|(x, w)| x * w)
+            .sum()
+    }
+    /// Pushes `input` to the regressor, and adapts the weights to bring the
+    /// output closer to `desired`. Returns the output and the error, both
+    /// before adapting.
+    pub fn step(&mut self, input: f64, desired: f64) -> (f64, f64) {
+        self.regressor.push(input);
+        let output = self.output();
+        let error = desired - output;
+        let (gain, decay) = match self.update {
+            Update::Lms { mu } => (mu * error, 1.0),
+            Update::Nlms { mu, eps } => {
+                let power: f64 = self.regressor.iter().map(|x| x * x).sum();
+                (mu * error / (eps + power), 1.0)
+            }
+            Update::Leaky { mu, leak } => (mu * error, 1.0 - mu * leak),
+        };
+        self.weights
+            .iter_mut()
+            .zip(self.regressor.iter())
+            .for_each(|(w, x)| *w = decay * *w + gain * x);
+        (output, error)
+    }
+    /// Returns the weights, where index `0` multiplies the newest input.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+    /// Returns the past inputs.
+    pub fn regressor(&self) -> &SlidingWindow<f64> {
+        &self.regressor
+    }
+}
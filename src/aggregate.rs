@@ -0,0 +1,388 @@
+//! Incremental aggregation over a [`SlidingWindow`].
+//!
+//! An [`Aggregate`] describes how to combine elements. If it also knows how to
+//! remove the element that a push evicts, the [`AggregateWindow`] keeps a
+//! single running value. Otherwise it falls back to the two stacks queue
+//! strategy, which is O(1) amortized as well, at the cost of 2N more items.
+use crate::{panics, SlidingWindow};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Mul, Not, Rem, Sub};
+
+#[cfg(test)]
+mod tests {
+    use super::{Aggregate, AggregateWindow, BitAndAll, Gcd, Max, Min, Product, Sum, Xor};
+    #[test]
+    fn sum() {
+        let mut aw = AggregateWindow::new(3, 1u32, Sum);
+        assert_eq!(aw.aggregate(), 3);
+        aw.push(5);
+        assert_eq!(aw.aggregate(), 7);
+        aw.push(7);
+        aw.push(9);
+        assert_eq!(aw.aggregate(), 21);
+        aw.push(0);
+        assert_eq!(aw.aggregate(), 16);
+        assert_eq!(*aw.window(), [0, 9, 7]);
+    }
+    #[test]
+    fn float_sum_recovers() {
+        let mut aw = AggregateWindow::new(4, 0.0f64, Sum);
+        aw.push(1e20);
+        for _ in 0..7 {
+            aw.push(0.3);
+        }
+        assert!((aw.aggregate() - 1.2).abs() < 1e-12);
+    }
+    #[test]
+    fn xor() {
+        let mut aw = AggregateWindow::new(2, 0u8, Xor);
+        aw.push(0b1010);
+        aw.push(0b0110);
+        assert_eq!(aw.aggregate(), 0b1100);
+        aw.push(0b0110);
+        assert_eq!(aw.aggregate(), 0);
+    }
+    #[test]
+    fn max_min() {
+        let data = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9];
+        let mut max = AggregateWindow::new(4, 0, Max(i32::MIN));
+        let mut min = AggregateWindow::new(4, 0, Min(i32::MAX));
+        for (i, x) in data.into_iter().enumerate() {
+            max.push(x);
+            min.push(x);
+            let start = i.saturating_sub(3);
+            let full = i >= 3;
+            let last = &data[start..=i];
+            let exp_max = *last.iter().max().unwrap();
+            let exp_min = if full { *last.iter().min().unwrap() } else { 0 };
+            assert_eq!(max.aggregate(), exp_max);
+            assert_eq!(min.aggregate(), exp_min);
+        }
+    }
+    #[test]
+    fn two_stacks_keeps_order() {
+        // string concatenation is neither invertible nor commutative
+        struct Concat;
+        impl Aggregate<String> for Concat {
+            fn identity(&self) -> String {
+                String::new()
+            }
+            fn combine(&self, a: &String, b: &String) -> String {
+                format!("{a}{b}")
+            }
+        }
+        let mut aw = AggregateWindow::new(3, "-".to_string(), Concat);
+        assert_eq!(aw.aggregate(), "---");
+        for s in ["a", "b", "c", "d", "e"] {
+            aw.push(s.to_string());
+        }
+        assert_eq!(aw.aggregate(), "cde");
+    }
+    #[test]
+    fn product_gcd_and() {
+        let mut aw = AggregateWindow::new(2, 1.0, Product);
+        aw.push(0.0);
+        aw.push(2.0);
+        aw.push(3.0);
+        assert_eq!(aw.aggregate(), 6.0);
+        let mut aw = AggregateWindow::new(3, 0u64, Gcd);
+        aw.push_slice(&[12, 18, 30]);
+        assert_eq!(aw.aggregate(), 6);
+        aw.push(45);
+        assert_eq!(aw.aggregate(), 3);
+        let mut aw = AggregateWindow::new(2, 0xffu8, BitAndAll);
+        aw.push(0b1100);
+        assert_eq!(aw.aggregate(), 0b1100);
+        aw.push(0b0110);
+        assert_eq!(aw.aggregate(), 0b0100);
+    }
+}
+
+/// An associative operation with an identity element, and optionally a way to
+/// undo it.
+pub trait Aggregate<T> {
+    /// The element that doesn't change the result of `combine`.
+    fn identity(&self) -> T;
+    /// Combines two aggregates, `a` holds the older elements and `b` the newer.
+    fn combine(&self, a: &T, b: &T) -> T;
+    /// Removes `evicted`, the oldest element, from `total`. Return `None` if
+    /// the operation can't be undone. It has to be consistent, either it always
+    /// returns `None` or never does.
+    fn inverse(&self, total: &T, evicted: &T) -> Option<T> {
+        let _ = (total, evicted);
+        None
+    }
+}
+
+/// How the aggregate is kept up to date.
+#[derive(Debug)]
+enum State<T> {
+    /// A running value, for invertible aggregates, and the pushes since it was
+    /// computed from the window.
+    Running(T, usize),
+    /// Two stacks, for any aggregate. `front` holds the aggregates of the
+    /// oldest elements from each one to the end of `front`, and `back` the
+    /// newest elements, whose aggregate is `back_agg`.
+    TwoStacks {
+        front: Vec<T>,
+        back: Vec<T>,
+        back_agg: T,
+    },
+}
+
+/// A [`SlidingWindow`] that keeps an [`Aggregate`] of its elements, in
+/// chronological order, so `aggregate` is O(1).
+///
+/// A running value is computed again from the window every `max_items`
+/// pushes, so an inverse that isn't exact, like the one of a floating point
+/// sum, only affects the aggregate until then.
+#[derive(Debug)]
+pub struct AggregateWindow<T, A>
+where
+    T: Clone,
+    A: Aggregate<T>,
+{
+    window: SlidingWindow<T>,
+    op: A,
+    state: State<T>,
+}
+
+impl<T, A> AggregateWindow<T, A>
+where
+    T: Clone,
+    A: Aggregate<T>,
+{
+    /// Same as [`SlidingWindow::new`], plus the operation to aggregate with.
+    pub fn new(max_items: usize, init: T, op: A) -> AggregateWindow<T, A> {
+        let window = SlidingWindow::new(max_items, init);
+        let identity = op.identity();
+        let total = fold(&op, &window);
+        let state = if op.inverse(&identity, &identity).is_some() {
+            State::Running(total, 0)
+        } else {
+            State::TwoStacks {
+                front: Vec::with_capacity(window.capacity()),
+                back: window.iter().rev().cloned().collect(),
+                back_agg: total,
+            }
+        };
+        AggregateWindow { window, op, state }
+    }
+    /// Push an element to the window, updating the aggregate.
+    pub fn push(&mut self, a: T) {
+        let evicted = self.window.push_pop(a.clone());
+        match &mut self.state {
+            State::Running(total, pushes) => {
+                *pushes += 1;
+                if *pushes == self.window.capacity() {
+                    *total = fold(&self.op, &self.window);
+                    *pushes = 0;
+                } else {
+                    let removed = self
+                        .op
+                        .inverse(total, &evicted)
+                        .expect(panics::INCONSISTENT_INVERSE);
+                    *total = self.op.combine(&removed, &a);
+                }
+            }
+            State::TwoStacks {
+                front,
+                back,
+                back_agg,
+            } => {
+                if front.is_empty() {
+                    let mut acc = self.op.identity();
+                    while let Some(x) = back.pop() {
+                        acc = self.op.combine(&x, &acc);
+                        front.push(acc.clone());
+                    }
+                    *back_agg = self.op.identity();
+                }
+                front.pop();
+                *back_agg = self.op.combine(back_agg, &a);
+                back.push(a);
+            }
+        }
+    }
+    /// Push a slice, where the newest item is at index 0.
+    pub fn push_slice(&mut self, a: &[T]) {
+        a.iter().rev().for_each(|a| {
+            self.push(a.to_owned());
+        });
+    }
+    /// Returns the aggregate of all the elements in the window.
+    pub fn aggregate(&self) -> T {
+        match &self.state {
+            State::Running(total, _) => total.clone(),
+            State::TwoStacks {
+                front, back_agg, ..
+            } => match front.last() {
+                Some(older) => self.op.combine(older, back_agg),
+                None => back_agg.clone(),
+            },
+        }
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<T> {
+        &self.window
+    }
+}
+
+/// Aggregate of the window, from the oldest element.
+fn fold<T: Clone, A: Aggregate<T>>(op: &A, window: &SlidingWindow<T>) -> T {
+    window
+        .iter()
+        .rev()
+        .fold(op.identity(), |acc, x| op.combine(&acc, x))
+}
+
+/// Sum of the elements, invertible. It is exact for integers. For floating
+/// point numbers, evicting a sample much bigger than the rest loses them from
+/// the sum until the window computes it again.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sum;
+
+impl<T> Aggregate<T> for Sum
+where
+    T: Clone + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn identity(&self) -> T {
+        T::default()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.clone() + b.clone()
+    }
+    fn inverse(&self, total: &T, evicted: &T) -> Option<T> {
+        Some(total.clone() - evicted.clone())
+    }
+}
+
+/// Product of the elements. It isn't invertible because of zeroes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Product;
+
+impl<T> Aggregate<T> for Product
+where
+    T: Clone + From<u8> + Mul<Output = T>,
+{
+    fn identity(&self) -> T {
+        T::from(1)
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.clone() * b.clone()
+    }
+}
+
+/// Bitwise xor of the elements, invertible, useful for checksums.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Xor;
+
+impl<T> Aggregate<T> for Xor
+where
+    T: Clone + Default + BitXor<Output = T>,
+{
+    fn identity(&self) -> T {
+        T::default()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.clone() ^ b.clone()
+    }
+    fn inverse(&self, total: &T, evicted: &T) -> Option<T> {
+        Some(total.clone() ^ evicted.clone())
+    }
+}
+
+/// Bitwise and of the elements.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitAndAll;
+
+impl<T> Aggregate<T> for BitAndAll
+where
+    T: Clone + Default + Not<Output = T> + BitAnd<Output = T>,
+{
+    fn identity(&self) -> T {
+        !T::default()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.clone() & b.clone()
+    }
+}
+
+/// Bitwise or of the elements.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitOrAll;
+
+impl<T> Aggregate<T> for BitOrAll
+where
+    T: Clone + Default + BitOr<Output = T>,
+{
+    fn identity(&self) -> T {
+        T::default()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        a.clone() | b.clone()
+    }
+}
+
+/// Greatest common divisor of the elements, meant for unsigned integers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gcd;
+
+impl<T> Aggregate<T> for Gcd
+where
+    T: Clone + Default + PartialEq + Rem<Output = T>,
+{
+    fn identity(&self) -> T {
+        T::default()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        let (mut a, mut b) = (a.clone(), b.clone());
+        while b != T::default() {
+            let r = a % b.clone();
+            a = b;
+            b = r;
+        }
+        a
+    }
+}
+
+/// Maximum of the elements. It holds the identity, a value that is lower or
+/// equal than any that will be pushed, like `i32::MIN` or `f64::NEG_INFINITY`.
+#[derive(Debug, Clone, Copy)]
+pub struct Max<T>(pub T);
+
+impl<T> Aggregate<T> for Max<T>
+where
+    T: Clone + PartialOrd,
+{
+    fn identity(&self) -> T {
+        self.0.clone()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        if b > a {
+            b.clone()
+        } else {
+            a.clone()
+        }
+    }
+}
+
+/// Minimum of the elements. It holds the identity, a value that is greater or
+/// equal than any that will be pushed, like `i32::MAX` or `f64::INFINITY`.
+#[derive(Debug, Clone, Copy)]
+pub struct Min<T>(pub T);
+
+impl<T> Aggregate<T> for Min<T>
+where
+    T: Clone + PartialOrd,
+{
+    fn identity(&self) -> T {
+        self.0.clone()
+    }
+    fn combine(&self, a: &T, b: &T) -> T {
+        if b < a {
+            b.clone()
+        } else {
+            a.clone()
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! A window of booleans packed one per bit, with the same order as a
+//! [`SlidingWindow`], where index `0` is the newest, and the queries that are
+//! usual on them, like counts, streaks and N out of M votes.
+use crate::{panics, SlidingWindow};
+use std::ops::Index;
+
+#[cfg(test)]
+mod tests {
+    use super::BitWindow;
+    use crate::SlidingWindow;
+    #[test]
+    fn follows_sliding_window() {
+        let mut bw = BitWindow::new(150, false);
+        let mut st = SlidingWindow::new(150, false);
+        for t in 0..1000u64 {
+            let bit = (t * t + t / 7) % 5 < 2;
+            assert_eq!(bw.push_pop(bit), st.push_pop(bit));
+            if t % 37 == 0 {
+                assert!(bw.iter().eq(st.iter().copied()));
+                assert_eq!(bw.count_ones(), st.iter().filter(|b| **b).count());
+                for m in [1, 10, 64, 65, 149, 150] {
+                    let ones = st.iter().take(m).filter(|b| **b).count();
+                    assert_eq!(bw.count_newest(m), ones);
+                    assert!(bw.n_of_m(ones, m) && !bw.n_of_m(ones + 1, m));
+                }
+                let streak = st.iter().take_while(|b| **b == st[0]).count();
+                assert_eq!(bw.current_streak(), (st[0], streak));
+                assert_eq!(bw[3], st[3]);
+            }
+        }
+    }
+    #[test]
+    fn streaks_and_runs() {
+        let bw = BitWindow::from([true, true, false, true, true, true, false]);
+        assert_eq!(bw.current_streak(), (true, 2));
+        assert_eq!(bw.longest_run(true), 3);
+        assert_eq!(bw.longest_run(false), 1);
+        assert_eq!(bw.count_zeros(), 2);
+        let mut bw = BitWindow::new(200, true);
+        assert_eq!(bw.current_streak(), (true, 200));
+        bw.push(false);
+        assert_eq!(bw.current_streak(), (false, 1));
+        assert_eq!(bw.longest_run(true), 199);
+        assert_eq!(bw, bw.iter().collect::<BitWindow>());
+    }
+    #[test]
+    #[should_panic(expected = "can't operate on empty SlidingWindow")]
+    fn from_empty() {
+        let _ = BitWindow::from([]);
+    }
+}
+
+/// A [`SlidingWindow`] of booleans, that keeps them in `u64` words and counts
+/// the `true` ones on every push.
+#[derive(Debug, Clone)]
+pub struct BitWindow {
+    words: Vec<u64>,
+    /// Bit where the next push goes.
+    head: usize,
+    capacity: usize,
+    ones: usize,
+}
+
+impl BitWindow {
+    /// Same as [`SlidingWindow::new`].
+    pub fn new(max_items: usize, init: bool) -> BitWindow {
+        let capacity = max_items.max(1);
+        let mut words = vec![if init { u64::MAX } else { 0 }; capacity.div_ceil(64)];
+        if !capacity.is_multiple_of(64) {
+            // unused bits are kept to zero
+            words[capacity / 64] &= (1 << (capacity % 64)) - 1;
+        }
+        BitWindow {
+            words,
+            head: 0,
+            capacity,
+            ones: if init { capacity } else { 0 },
+        }
+    }
+    fn bit(&self, pos: usize) -> bool {
+        self.words[pos / 64] >> (pos % 64) & 1 == 1
+    }
+    /// Physical position of index `i`.
+    fn position(&self, i: usize) -> usize {
+        (self.head + self.capacity - 1 - i % self.capacity) % self.capacity
+    }
+    /// Push an element to the window, forgetting the oldest.
+    pub fn push(&mut self, a: bool) {
+        self.push_pop(a);
+    }
+    /// Push an element to the window, returning the oldest, which is the one
+    /// forgotten.
+    pub fn push_pop(&mut self, a: bool) -> bool {
+        let old = self.bit(self.head);
+        let mask = 1 << (self.head % 64);
+        if a {
+            self.words[self.head / 64] |= mask;
+        } else {
+            self.words[self.head / 64] &= !mask;
+        }
+        self.ones = self.ones + a as usize - old as usize;
+        self.head = (self.head + 1) % self.capacity;
+        old
+    }
+    /// Same as [`SlidingWindow::capacity`].
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Returns the element at `index`, where `0` is the newest.
+    pub fn get(&self, index: usize) -> bool {
+        self.bit(self.position(index))
+    }
+    /// Returns an ordered iterator, where the first element is the newest and
+    /// the last, the oldest.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.capacity).map(|i| self.get(i))
+    }
+    /// Returns the amount of `true` elements. This operation is O(1).
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+    /// Returns the amount of `false` elements. This operation is O(1).
+    pub fn count_zeros(&self) -> usize {
+        self.capacity - self.ones
+    }
+    /// Amount of ones in the physical bits `start..end`.
+    fn count_range(&self, start: usize, end: usize) -> usize {
+        if start >= end {
+            return 0;
+        }
+        let (first, last) = (start / 64, (end - 1) / 64);
+        (first..=last)
+            .map(|w| {
+                let mut word = self.words[w];
+                if w == first {
+                    word &= u64::MAX << (start % 64);
+                }
+                if w == last && !end.is_multiple_of(64) {
+                    word &= (1 << (end % 64)) - 1;
+                }
+                word.count_ones() as usize
+            })
+            .sum()
+    }
+    /// Returns the amount of `true` elements among the newest `m`, or all of
+    /// them if `m` is bigger than the capacity. This operation is O(m / 64).
+    pub fn count_newest(&self, m: usize) -> usize {
+        let m = m.min(self.capacity);
+        if m <= self.head {
+            self.count_range(self.head - m, self.head)
+        } else {
+            let wrapped = m - self.head;
+            self.count_range(0, self.head)
+                + self.count_range(self.capacity - wrapped, self.capacity)
+        }
+    }
+    /// Returns whether at least `n` of the newest `m` elements are `true`.
+    pub fn n_of_m(&self, n: usize, m: usize) -> bool {
+        self.count_newest(m) >= n
+    }
+    /// Returns the newest element and how many elements in a row, from the
+    /// newest, are equal to it. This operation is O(streak / 64).
+    pub fn current_streak(&self) -> (bool, usize) {
+        let value = self.get(0);
+        let mut pos = self.position(0);
+        let mut streak = 0;
+        while streak < self.capacity {
+            let word = self.words[pos / 64];
+            let word = if value { !word } else { word };
+            let bit = pos % 64;
+            // bits from `pos` downwards, at the top
+            let same = ((word << (63 - bit)).leading_zeros() as usize).min(bit + 1);
+            streak += same;
+            if same <= bit {
+                break;
+            }
+            pos = if pos < 64 {
+                self.capacity - 1
+            } else {
+                pos - same
+            };
+        }
+        (value, streak.min(self.capacity))
+    }
+    /// Returns the length of the longest run of elements equal to `value`.
+    /// This operation is O(n).
+    pub fn longest_run(&self, value: bool) -> usize {
+        let mut run = 0;
+        self.iter()
+            .map(|b| {
+                run = if b == value { run + 1 } else { 0 };
+                run
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    /// Returns a [`SlidingWindow`] with the same elements.
+    pub fn to_sliding_window(&self) -> SlidingWindow<bool> {
+        self.iter().collect()
+    }
+}
+
+/// Same as the [`FromIterator`] of [`SlidingWindow`], the first element is the
+/// newest.
+impl FromIterator<bool> for BitWindow {
+    fn from_iter<Q: IntoIterator<Item = bool>>(iter: Q) -> Self {
+        let bits: Vec<bool> = iter.into_iter().collect();
+        let mut bw = BitWindow::new(bits.len(), false);
+        bits.into_iter().rev().for_each(|b| bw.push(b));
+        bw
+    }
+}
+
+impl<const LEN: usize> From<[bool; LEN]> for BitWindow {
+    fn from(a: [bool; LEN]) -> Self {
+        if a.is_empty() {
+            panic!("{}", panics::START_EMPTY);
+        }
+        a.into_iter().collect()
+    }
+}
+
+impl From<&SlidingWindow<bool>> for BitWindow {
+    fn from(a: &SlidingWindow<bool>) -> Self {
+        a.iter().copied().collect()
+    }
+}
+
+impl PartialEq for BitWindow {
+    fn eq(&self, other: &Self) -> bool {
+        self.capacity == other.capacity && self.iter().eq(other.iter())
+    }
+}
+
+impl Index<usize> for BitWindow {
+    type Output = bool;
+    fn index(&self, index: usize) -> &Self::Output {
+        if self.get(index) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Detection of changes in the mean of a stream of samples, for instance to
+//! reset estimators when the process changes regime.
+//!
+//! Every detector has a `push` that returns a [`Change`] when it finds one,
+//! with the estimated amount of samples since it happened.
+use crate::{SlidingWindow, CANCELLATION};
+
+#[cfg(test)]
+mod tests {
+    use super::{Change, Cusum, Direction, MeanShift, PageHinkley};
+    use crate::test_noise as noisy;
+    /// Pushes a step of `height` at sample 200, returning the sample at which
+    /// it was detected and the change.
+    fn step<F: FnMut(f64) -> Option<Change>>(height: f64, mut push: F) -> (usize, Change) {
+        for t in 0..400 {
+            let x = noisy(t) + if t < 200 { 5.0 } else { 5.0 + height };
+            if let Some(change) = push(x) {
+                return (t, change);
+            }
+        }
+        panic!("no change detected");
+    }
+    #[test]
+    fn cusum() {
+        let mut cusum = Cusum::new(5.0, 0.25, 2.0);
+        let (t, change) = step(1.0, |x| cusum.push(x));
+        assert!((200..210).contains(&t));
+        assert_eq!(t - change.ago, 200);
+        assert_eq!(change.direction, Direction::Increase);
+        assert_eq!(cusum.statistics(), (0.0, 0.0));
+        cusum.reset_target(6.0);
+        let (_, change) = step(-2.0, |x| cusum.push(x + 1.0));
+        assert_eq!(change.direction, Direction::Decrease);
+    }
+    #[test]
+    fn page_hinkley() {
+        let mut ph = PageHinkley::new(0.25, 3.0);
+        let (t, change) = step(-1.0, |x| ph.push(x));
+        assert!((200..220).contains(&t));
+        assert!((t - change.ago).abs_diff(200) <= 1);
+        assert_eq!(change.direction, Direction::Decrease);
+    }
+    #[test]
+    fn mean_shift() {
+        let mut ms = MeanShift::new(60, 5.0, 10, 5.0);
+        let (t, change) = step(1.0, |x| ms.push(x));
+        assert!((200..210).contains(&t));
+        assert_eq!(t - change.ago, 200);
+        assert_eq!(change.direction, Direction::Increase);
+        // only reported once
+        for t in 0..100 {
+            assert!(ms.push(6.0 + noisy(t)).is_none());
+        }
+        assert!(ms.statistic().abs() < 5.0);
+        assert_eq!(MeanShift::new(1, 0.0, 2, 5.0).window().capacity(), 4);
+    }
+    #[test]
+    fn mean_shift_after_transient() {
+        let mut ms = MeanShift::new(60, 0.0, 10, 5.0);
+        for t in 0..100 {
+            ms.push(if t % 2 == 0 { 0.0 } else { 1e9 });
+        }
+        for t in 0..1000 {
+            let change = ms.push(noisy(t));
+            // once the transient has left the window
+            if t >= 60 {
+                assert!(change.is_none(), "{t}");
+                assert!(ms.statistic().abs() < 5.0);
+            }
+        }
+    }
+}
+
+/// Which way the mean changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The mean went up.
+    Increase,
+    /// The mean went down.
+    Decrease,
+}
+
+/// A detected change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    /// Estimated samples since the change, where `0` means that the newest
+    /// sample is the first after it, like the indexes of a window.
+    pub ago: usize,
+    /// Which way the mean changed.
+    pub direction: Direction,
+}
+
+/// Two-sided tabular CUSUM, that accumulates the deviations from a target
+/// beyond a `drift`, and detects a change when one of the sums goes over a
+/// `threshold`. The sums restart after a change.
+///
+/// In units of the standard deviation, a `drift` of half the shift to detect
+/// and a `threshold` of 4 or 5 are the usual choices.
+#[derive(Debug, Clone)]
+pub struct Cusum {
+    target: f64,
+    drift: f64,
+    threshold: f64,
+    high: f64,
+    low: f64,
+    /// Samples since each sum was last zero.
+    high_run: usize,
+    low_run: usize,
+}
+
+impl Cusum {
+    /// Creates a detector of changes away from `target`.
+    pub fn new(target: f64, drift: f64, threshold: f64) -> Cusum {
+        Cusum {
+            target,
+            drift,
+            threshold,
+            high: 0.0,
+            low: 0.0,
+            high_run: 0,
+            low_run: 0,
+        }
+    }
+    /// Adds a sample, returning the change if one of the sums goes over the
+    /// threshold, dated at the first sample of that sum.
+    pub fn push(&mut self, x: f64) -> Option<Change> {
+        let deviation = x - self.target;
+        self.high = (self.high + deviation - self.drift).max(0.0);
+        self.low = (self.low - deviation - self.drift).max(0.0);
+        self.high_run = if self.high > 0.0 {
+            self.high_run + 1
+        } else {
+            0
+        };
+        self.low_run = if self.low > 0.0 { self.low_run + 1 } else { 0 };
+        let change = if self.high > self.threshold {
+            Some((self.high_run, Direction::Increase))
+        } else if self.low > self.threshold {
+            Some((self.low_run, Direction::Decrease))
+        } else {
+            None
+        };
+        change.map(|(run, direction)| {
+            self.reset();
+            Change {
+                ago: run - 1,
+                direction,
+            }
+        })
+    }
+    /// Restarts the sums.
+    pub fn reset(&mut self) {
+        self.high = 0.0;
+        self.low = 0.0;
+        self.high_run = 0;
+        self.low_run = 0;
+    }
+    /// Restarts the sums around a new `target`, usually the mean after a
+    /// change.
+    pub fn reset_target(&mut self, target: f64) {
+        self.target = target;
+        self.reset();
+    }
+    /// Returns the upper and lower sums.
+    pub fn statistics(&self) -> (f64, f64) {
+        (self.high, self.low)
+    }
+}
+
+/// Two-sided Page-Hinkley test, that accumulates the deviations from the
+/// running mean beyond a tolerance `delta`, and detects a change when the sum
+/// moves more than `lambda` away from its extreme. It has no target, the mean
+/// is learnt from the samples since the last change.
+#[derive(Debug, Clone)]
+pub struct PageHinkley {
+    delta: f64,
+    lambda: f64,
+    count: usize,
+    mean: f64,
+    /// Sums for increases and decreases, with their extremes and when they
+    /// were reached.
+    up: f64,
+    up_min: (f64, usize),
+    down: f64,
+    down_max: (f64, usize),
+}
+
+impl PageHinkley {
+    /// Creates a detector that tolerates deviations of `delta` and detects
+    /// changes whose accumulated deviation reaches `lambda`.
+    pub fn new(delta: f64, lambda: f64) -> PageHinkley {
+        PageHinkley {
+            delta,
+            lambda,
+            count: 0,
+            mean: 0.0,
+            up: 0.0,
+            up_min: (0.0, 0),
+            down: 0.0,
+            down_max: (0.0, 0),
+        }
+    }
+    /// Adds a sample, returning the change if there is one, dated at the
+    /// extreme of the sum.
+    pub fn push(&mut self, x: f64) -> Option<Change> {
+        self.count += 1;
+        self.mean += (x - self.mean) / self.count as f64;
+        self.up += x - self.mean - self.delta;
+        self.down += x - self.mean + self.delta;
+        if self.up < self.up_min.0 {
+            self.up_min = (self.up, self.count);
+        }
+        if self.down > self.down_max.0 {
+            self.down_max = (self.down, self.count);
+        }
+        let change = if self.up - self.up_min.0 > self.lambda {
+            Some((self.up_min.1, Direction::Increase))
+        } else if self.down_max.0 - self.down > self.lambda {
+            Some((self.down_max.1, Direction::Decrease))
+        } else {
+            None
+        };
+        change.map(|(extreme, direction)| {
+            let ago = self.count - extreme - 1;
+            self.reset();
+            Change { ago, direction }
+        })
+    }
+    /// Forgets all the samples.
+    pub fn reset(&mut self) {
+        *self = PageHinkley::new(self.delta, self.lambda);
+    }
+    /// Returns the mean of the samples since the last change.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+}
+
+/// A [`SlidingWindow`] split in the newest `recent` samples and the older
+/// rest, that detects a change when Welch's t statistic of the difference of
+/// their means goes over a threshold.
+///
+/// A change is reported once, when the statistic crosses the threshold, and
+/// dated at the split of the window that separates the means the most.
+///
+/// The sums of both parts are computed again from the window every
+/// `max_items` pushes, and as soon as a huge transient leaves either part, so
+/// that it doesn't leave rounding errors behind.
+#[derive(Debug, Clone)]
+pub struct MeanShift {
+    window: SlidingWindow<f64>,
+    recent: usize,
+    threshold: f64,
+    /// Sums and sums of squares of the recent and older samples.
+    sums: [f64; 2],
+    squares: [f64; 2],
+    /// Pushes since the sums were computed from the window.
+    pushes: usize,
+    /// Largest sums of squares since then.
+    peaks: [f64; 2],
+    alarm: bool,
+}
+
+impl MeanShift {
+    /// Same as [`SlidingWindow::new`], but with a capacity of at least 4, so
+    /// that both parts have two samples. The newest `recent` samples, between
+    /// `2` and the capacity minus `2`, are compared against the rest.
+    pub fn new(max_items: usize, init: f64, recent: usize, threshold: f64) -> MeanShift {
+        let window = SlidingWindow::new(max_items.max(4), init);
+        let n = window.capacity();
+        let mut ms = MeanShift {
+            window,
+            recent: recent.clamp(2, n - 2),
+            threshold,
+            sums: [0.0; 2],
+            squares: [0.0; 2],
+            pushes: 0,
+            peaks: [0.0; 2],
+            alarm: false,
+        };
+        ms.recompute();
+        ms
+    }
+    fn recompute(&mut self) {
+        self.sums = [0.0; 2];
+        self.squares = [0.0; 2];
+        for (i, x) in self.window.iter().enumerate() {
+            let part = (i >= self.recent) as usize;
+            self.sums[part] += x;
+            self.squares[part] += x * x;
+        }
+        self.pushes = 0;
+        self.peaks = self.squares;
+    }
+    /// Push a sample, returning the change if the statistic crosses the
+    /// threshold.
+    pub fn push(&mut self, x: f64) -> Option<Change> {
+        let evicted = self.window.push_pop(x);
+        // the oldest recent sample becomes the newest of the rest
+        let moved = self.window[self.recent];
+        self.sums[0] += x - moved;
+        self.squares[0] += x * x - moved * moved;
+        self.sums[1] += moved - evicted;
+        self.squares[1] += moved * moved - evicted * evicted;
+        self.pushes += 1;
+        self.peaks = [0, 1].map(|i| self.peaks[i].max(self.squares[i]));
+        if self.pushes == self.window.capacity()
+            || (0..2).any(|i| self.squares[i] < CANCELLATION * self.peaks[i])
+        {
+            self.recompute();
+        }
+        let statistic = self.statistic();
+        let alarm = statistic.abs() > self.threshold;
+        let rising = alarm && !self.alarm;
+        self.alarm = alarm;
+        rising.then(|| Change {
+            ago: self.split(),
+            direction: if statistic > 0.0 {
+                Direction::Increase
+            } else {
+                Direction::Decrease
+            },
+        })
+    }
+    /// Returns Welch's t statistic, positive if the recent mean is higher.
+    pub fn statistic(&self) -> f64 {
+        let n = [
+            self.recent as f64,
+            (self.window.capacity() - self.recent) as f64,
+        ];
+        let mean = [self.sums[0] / n[0], self.sums[1] / n[1]];
+        let var =
+            [0, 1].map(|i| ((self.squares[i] - n[i] * mean[i] * mean[i]) / (n[i] - 1.0)).max(0.0));
+        let diff = mean[0] - mean[1];
+        let se = (var[0] / n[0] + var[1] / n[1]).sqrt();
+        if se > 0.0 {
+            diff / se
+        } else if diff == 0.0 {
+            0.0
+        } else {
+            diff.signum() * f64::INFINITY
+        }
+    }
+    /// Returns the index of the oldest sample after the split that maximizes
+    /// the difference of means, weighted by the sizes of both sides.
+    fn split(&self) -> usize {
+        let n = self.window.capacity();
+        let total: f64 = self.window.iter().sum();
+        let mut newer = 0.0;
+        let mut best = (0.0, 0);
+        for (i, x) in self.window.iter().take(n - 1).enumerate() {
+            newer += x;
+            let k = (i + 1) as f64;
+            let rest = (n - i - 1) as f64;
+            let score = (newer / k - (total - newer) / rest).abs() * (k * rest).sqrt();
+            if score > best.0 {
+                best = (score, i);
+            }
+        }
+        best.1
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
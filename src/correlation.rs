@@ -0,0 +1,321 @@
+//! Covariance and correlation between two synchronized windows, where index
+//! `i` of both holds samples taken at the same time.
+use crate::{panics, SlidingWindow, CANCELLATION};
+use std::ops::RangeInclusive;
+
+#[cfg(test)]
+mod tests {
+    use super::{covariance, cross_correlation, pearson, time_delay, CorrelationWindow};
+    use crate::SlidingWindow;
+    fn signal(t: usize) -> f64 {
+        ((t * 7919) % 101) as f64 / 10.0 + (t as f64 / 5.0).sin()
+    }
+    #[test]
+    fn batch() {
+        let a = SlidingWindow::from([1.0, 2.0, 3.0, 4.0]);
+        let b = SlidingWindow::from([2.0, 4.0, 6.0, 8.0]);
+        let c = SlidingWindow::from([4.0, 3.0, 2.0, 1.0]);
+        assert_eq!(covariance(&a, &b), 2.5);
+        assert!((pearson(&a, &b).unwrap() - 1.0).abs() < 1e-12);
+        assert!((pearson(&a, &c).unwrap() + 1.0).abs() < 1e-12);
+        assert!(pearson(&a, &SlidingWindow::new(4, 1.0)).is_none());
+    }
+    #[test]
+    #[should_panic(expected = "windows must have the same capacity")]
+    fn different_capacity() {
+        covariance(&SlidingWindow::new(2, 0.0), &SlidingWindow::new(3, 0.0));
+    }
+    #[test]
+    fn incremental() {
+        let mut cw = CorrelationWindow::new(50, 0.0, 0.0);
+        for t in 0..300 {
+            cw.push(signal(t), 3.0 * signal(t + 11) - 1.0);
+            let cov = covariance(cw.x(), cw.y());
+            assert!((cw.covariance() - cov).abs() < 1e-9);
+            if t > 50 {
+                let r = pearson(cw.x(), cw.y()).unwrap();
+                assert!((cw.pearson().unwrap() - r).abs() < 1e-9);
+            }
+        }
+    }
+    #[test]
+    fn long_run() {
+        let mut cw = CorrelationWindow::new(10, 0.0, 0.0);
+        for t in 0..1_000_003 {
+            cw.push(1e6 + signal(t), 1e6 - signal(t + 3));
+        }
+        let r = pearson(cw.x(), cw.y()).unwrap();
+        assert!((cw.pearson().unwrap() - r).abs() < 1e-9);
+        assert!((cw.covariance() - covariance(cw.x(), cw.y())).abs() < 1e-6);
+        for _ in 0..10 {
+            cw.push(1.0, 2.0);
+        }
+        assert!(cw.pearson().is_none());
+        assert_eq!(cw.covariance(), 0.0);
+    }
+    #[test]
+    fn dead_time() {
+        let mut action = SlidingWindow::new(200, 0.0);
+        let mut response = SlidingWindow::new(200, 0.0);
+        for t in 0..400 {
+            action.push(signal(t));
+            response.push(0.5 * signal(t.saturating_sub(7)) + 2.0);
+        }
+        let (lag, r) = time_delay(&action, &response, 20).unwrap();
+        assert_eq!(lag, 7);
+        assert!((r - 1.0).abs() < 1e-9);
+        let xc = cross_correlation(&action, &response, -3..=3);
+        assert_eq!(xc.len(), 7);
+        assert_eq!(xc[0].0, -3);
+        assert!(xc.iter().all(|(_, x)| *x < r - 0.1));
+    }
+}
+
+/// Running co-moments of pairs of samples, with Welford's updates, which can
+/// also remove samples.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Comoments {
+    pub(crate) n: f64,
+    pub(crate) mean_x: f64,
+    pub(crate) mean_y: f64,
+    pub(crate) m2_x: f64,
+    pub(crate) m2_y: f64,
+    pub(crate) c_xy: f64,
+}
+
+impl Comoments {
+    pub(crate) fn add(&mut self, x: f64, y: f64) {
+        self.n += 1.0;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / self.n;
+        let old_y = self.mean_y;
+        self.mean_y += (y - old_y) / self.n;
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += (y - old_y) * (y - self.mean_y);
+        self.c_xy += dx * (y - self.mean_y);
+    }
+    pub(crate) fn remove(&mut self, x: f64, y: f64) {
+        if self.n <= 1.0 {
+            *self = Comoments::default();
+            return;
+        }
+        let (old_x, old_y) = (self.mean_x, self.mean_y);
+        self.n -= 1.0;
+        self.mean_x = old_x - (x - old_x) / self.n;
+        self.mean_y = old_y - (y - old_y) / self.n;
+        self.m2_x -= (x - self.mean_x) * (x - old_x);
+        self.m2_y -= (y - self.mean_y) * (y - old_y);
+        self.c_xy -= (x - self.mean_x) * (y - old_y);
+    }
+    pub(crate) fn covariance(&self) -> f64 {
+        self.c_xy / self.n
+    }
+    pub(crate) fn pearson(&self) -> Option<f64> {
+        let den = (self.m2_x * self.m2_y).sqrt();
+        if den > 0.0 {
+            Some((self.c_xy / den).clamp(-1.0, 1.0))
+        } else {
+            None
+        }
+    }
+}
+
+impl FromIterator<(f64, f64)> for Comoments {
+    fn from_iter<Q: IntoIterator<Item = (f64, f64)>>(iter: Q) -> Self {
+        let mut m = Comoments::default();
+        iter.into_iter().for_each(|(x, y)| m.add(x, y));
+        m
+    }
+}
+
+/// Two synchronized windows with their [`Comoments`].
+///
+/// Removing the evicted samples accumulates rounding errors, so the moments
+/// are computed again from the windows every `capacity` pushes, which is still
+/// O(1) amortized. They are also computed again when evicting drops the spread
+/// of either window far below the largest since then, as after a huge sample
+/// leaves, since the remainder would be mostly rounding error.
+#[derive(Debug, Clone)]
+pub(crate) struct PairedWindows {
+    pub(crate) x: SlidingWindow<f64>,
+    pub(crate) y: SlidingWindow<f64>,
+    pub(crate) moments: Comoments,
+    /// Pushes since the moments were computed from the windows.
+    pushes: usize,
+    /// Largest `m2_x` and `m2_y` since then.
+    peaks: (f64, f64),
+}
+
+impl PairedWindows {
+    /// Pairs two windows of the same capacity.
+    pub(crate) fn new(x: SlidingWindow<f64>, y: SlidingWindow<f64>) -> PairedWindows {
+        same_capacity(&x, &y);
+        let mut pairs = PairedWindows {
+            x,
+            y,
+            moments: Comoments::default(),
+            pushes: 0,
+            peaks: (0.0, 0.0),
+        };
+        pairs.recompute();
+        pairs
+    }
+    fn recompute(&mut self) {
+        self.moments = self.x.iter().copied().zip(self.y.iter().copied()).collect();
+        self.pushes = 0;
+        self.peaks = (self.moments.m2_x, self.moments.m2_y);
+    }
+    /// Push a pair of samples, updating the moments.
+    pub(crate) fn push(&mut self, x: f64, y: f64) {
+        let old_x = self.x.push_pop(x);
+        let old_y = self.y.push_pop(y);
+        self.pushes += 1;
+        self.moments.remove(old_x, old_y);
+        self.moments.add(x, y);
+        let m = &self.moments;
+        self.peaks = (self.peaks.0.max(m.m2_x), self.peaks.1.max(m.m2_y));
+        if self.pushes == self.x.capacity()
+            || m.m2_x < CANCELLATION * self.peaks.0
+            || m.m2_y < CANCELLATION * self.peaks.1
+        {
+            self.recompute();
+        }
+    }
+}
+
+/// Checks that both windows can be paired up.
+fn same_capacity(a: &SlidingWindow<f64>, b: &SlidingWindow<f64>) {
+    if a.capacity() != b.capacity() {
+        panic!("{}", panics::CAPACITY_MISMATCH);
+    }
+}
+
+/// Returns the population covariance of both windows.
+///
+/// # Panics
+///
+/// If the windows don't have the same capacity.
+pub fn covariance(a: &SlidingWindow<f64>, b: &SlidingWindow<f64>) -> f64 {
+    same_capacity(a, b);
+    a.iter()
+        .copied()
+        .zip(b.iter().copied())
+        .collect::<Comoments>()
+        .covariance()
+}
+
+/// Returns the Pearson correlation coefficient of both windows, or `None` if
+/// either of them is constant.
+///
+/// # Panics
+///
+/// If the windows don't have the same capacity.
+pub fn pearson(a: &SlidingWindow<f64>, b: &SlidingWindow<f64>) -> Option<f64> {
+    same_capacity(a, b);
+    a.iter()
+        .copied()
+        .zip(b.iter().copied())
+        .collect::<Comoments>()
+        .pearson()
+}
+
+/// Pearson correlation between `a` and `b` delayed `lag` samples, over the
+/// samples where they overlap.
+fn lagged(a: &SlidingWindow<f64>, b: &SlidingWindow<f64>, lag: isize) -> Option<f64> {
+    let skip = lag.unsigned_abs();
+    if lag >= 0 {
+        // b[i] happened lag samples after a[i + lag]
+        a.iter()
+            .skip(skip)
+            .copied()
+            .zip(b.iter().copied())
+            .collect::<Comoments>()
+            .pearson()
+    } else {
+        a.iter()
+            .copied()
+            .zip(b.iter().skip(skip).copied())
+            .collect::<Comoments>()
+            .pearson()
+    }
+}
+
+/// Returns the Pearson correlation between `a` and `b` delayed by every lag in
+/// `lags`, computed over the part of the windows that overlaps. Lags where it
+/// isn't defined are left out. A positive lag means that `b` follows `a`.
+///
+/// # Panics
+///
+/// If the windows don't have the same capacity.
+pub fn cross_correlation(
+    a: &SlidingWindow<f64>,
+    b: &SlidingWindow<f64>,
+    lags: RangeInclusive<isize>,
+) -> Vec<(isize, f64)> {
+    same_capacity(a, b);
+    lags.filter_map(|lag| lagged(a, b, lag).map(|r| (lag, r)))
+        .collect()
+}
+
+/// Estimates by how many samples `b` follows `a`, looking at lags up to
+/// `max_lag` in both directions. Returns the lag of maximum correlation and
+/// the correlation, or `None` if it isn't defined for any lag.
+///
+/// # Panics
+///
+/// If the windows don't have the same capacity.
+pub fn time_delay(
+    a: &SlidingWindow<f64>,
+    b: &SlidingWindow<f64>,
+    max_lag: usize,
+) -> Option<(isize, f64)> {
+    let max_lag = max_lag.min(a.capacity().saturating_sub(2)) as isize;
+    cross_correlation(a, b, -max_lag..=max_lag)
+        .into_iter()
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+}
+
+/// Two synchronized windows, that keep their covariance and correlation up to
+/// date in O(1) per push.
+#[derive(Debug, Clone)]
+pub struct CorrelationWindow {
+    pairs: PairedWindows,
+}
+
+impl CorrelationWindow {
+    /// Creates both windows with `max_items`, initialized to `init_x` and
+    /// `init_y`.
+    pub fn new(max_items: usize, init_x: f64, init_y: f64) -> CorrelationWindow {
+        CorrelationWindow {
+            pairs: PairedWindows::new(
+                SlidingWindow::new(max_items, init_x),
+                SlidingWindow::new(max_items, init_y),
+            ),
+        }
+    }
+    /// Push a pair of samples taken at the same time.
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.pairs.push(x, y);
+    }
+    /// Returns the population covariance.
+    pub fn covariance(&self) -> f64 {
+        self.pairs.moments.covariance()
+    }
+    /// Returns the Pearson correlation coefficient, or `None` if either
+    /// window is constant.
+    pub fn pearson(&self) -> Option<f64> {
+        self.pairs.moments.pearson()
+    }
+    /// Returns the means of both windows.
+    pub fn means(&self) -> (f64, f64) {
+        (self.pairs.moments.mean_x, self.pairs.moments.mean_y)
+    }
+    /// Returns the first window.
+    pub fn x(&self) -> &SlidingWindow<f64> {
+        &self.pairs.x
+    }
+    /// Returns the second window.
+    pub fn y(&self) -> &SlidingWindow<f64> {
+        &self.pairs.y
+    }
+}
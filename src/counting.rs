@@ -0,0 +1,144 @@
+//! Multiset of the contents of a [`SlidingWindow`], to count how many times
+//! every item is in it without going through the window.
+use crate::SlidingWindow;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+#[cfg(test)]
+mod tests {
+    use super::CountingWindow;
+    #[test]
+    fn counts() {
+        let mut cw = CountingWindow::new(5, "ok");
+        assert_eq!(cw.count(&"ok"), 5);
+        assert_eq!(cw.mode(), (&"ok", 5));
+        for code in ["E1", "E2", "E1", "E3"] {
+            cw.push(code);
+        }
+        assert_eq!(cw.count(&"ok"), 1);
+        assert_eq!(cw.count(&"E1"), 2);
+        assert_eq!(cw.count(&"E4"), 0);
+        assert_eq!(cw.distinct(), 4);
+        assert_eq!(cw.mode(), (&"E1", 2));
+        cw.push("E2");
+        cw.push("E2");
+        assert_eq!(cw.mode(), (&"E2", 3));
+        assert_eq!(cw.distinct(), 3);
+        let top = cw.top_k(2);
+        assert_eq!(top[0], (&"E2", 3));
+        assert_eq!(top[1].1, 1);
+        assert_eq!(cw.top_k(10).len(), 3);
+    }
+    #[test]
+    fn matches_window() {
+        let mut cw = CountingWindow::new(50, 0);
+        for t in 0..1000u64 {
+            cw.push(t * t % 17);
+            if t % 97 == 0 {
+                for x in 0..17 {
+                    let n = cw.window().iter().filter(|y| **y == x).count();
+                    assert_eq!(cw.count(&x), n);
+                }
+                let (_, mode) = cw.mode();
+                let max = (0..17).map(|x| cw.count(&x)).max().unwrap();
+                assert_eq!(mode, max);
+                assert_eq!(cw.counts().values().sum::<usize>(), 50);
+            }
+        }
+    }
+}
+
+/// A [`SlidingWindow`] with the amount of times every item is in it, kept up
+/// to date on every push.
+///
+/// `count` and `mode` are O(1), and `top_k` only visits the counts from the
+/// highest down to the one of the `k`th item. Ties between items are broken
+/// arbitrarily.
+#[derive(Debug, Clone)]
+pub struct CountingWindow<T: Clone + Hash + Eq> {
+    window: SlidingWindow<T>,
+    counts: HashMap<T, usize>,
+    /// The items with each count, indexed by it.
+    by_count: Vec<HashSet<T>>,
+    max_count: usize,
+}
+
+impl<T: Clone + Hash + Eq> CountingWindow<T> {
+    /// Same as [`SlidingWindow::new`].
+    pub fn new(max_items: usize, init: T) -> CountingWindow<T> {
+        let window = SlidingWindow::new(max_items, init.clone());
+        let n = window.capacity();
+        let mut by_count = vec![HashSet::new(); n + 1];
+        by_count[n].insert(init.clone());
+        CountingWindow {
+            window,
+            counts: HashMap::from([(init, n)]),
+            by_count,
+            max_count: n,
+        }
+    }
+    /// Push an element to the window, forgetting the oldest.
+    pub fn push(&mut self, a: T) {
+        let evicted = self.window.push_pop(a.clone());
+        if evicted == a {
+            return;
+        }
+        let count = self
+            .counts
+            .get_mut(&evicted)
+            .expect("evicted items are counted");
+        self.by_count[*count].remove(&evicted);
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(&evicted);
+        } else {
+            self.by_count[*count].insert(evicted);
+        }
+        if self.by_count[self.max_count].is_empty() {
+            self.max_count -= 1;
+        }
+        let count = self.counts.entry(a.clone()).or_insert(0);
+        if *count > 0 {
+            self.by_count[*count].remove(&a);
+        }
+        *count += 1;
+        self.max_count = self.max_count.max(*count);
+        self.by_count[*count].insert(a);
+    }
+    /// Returns how many times `a` is in the window.
+    pub fn count(&self, a: &T) -> usize {
+        self.counts.get(a).copied().unwrap_or(0)
+    }
+    /// Returns the most frequent item and its count.
+    pub fn mode(&self) -> (&T, usize) {
+        let item = self.by_count[self.max_count]
+            .iter()
+            .next()
+            .expect("the highest count has items");
+        (item, self.max_count)
+    }
+    /// Returns the amount of different items in the window.
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+    /// Returns up to `k` of the most frequent items, with their counts, from
+    /// the most frequent.
+    pub fn top_k(&self, k: usize) -> Vec<(&T, usize)> {
+        self.by_count[..=self.max_count]
+            .iter()
+            .enumerate()
+            .rev()
+            .take_while(|(c, _)| *c > 0)
+            .flat_map(|(c, items)| items.iter().map(move |item| (item, c)))
+            .take(k)
+            .collect()
+    }
+    /// Returns the count of every item in the window.
+    pub fn counts(&self) -> &HashMap<T, usize> {
+        &self.counts
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<T> {
+        &self.window
+    }
+}
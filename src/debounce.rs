@@ -0,0 +1,300 @@
+//! Conditioning of digital and analog inputs, like the blocks of a PLC:
+//! debouncers, on and off delays and a Schmitt trigger.
+//!
+//! Every block has a `push` that takes the new sample and returns the
+//! conditioned output, and an `output` to read it again. Delays are counted in
+//! samples.
+use crate::bits::BitWindow;
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::{IntegratorDebouncer, MajorityDebouncer, OffDelay, OnDelay, SchmittTrigger};
+    const BOUNCY: [bool; 12] = [
+        true, false, true, true, false, true, true, true, false, false, true, false,
+    ];
+    #[test]
+    fn majority() {
+        let mut md = MajorityDebouncer::new(5, false);
+        let out: Vec<bool> = BOUNCY.iter().map(|x| md.push(*x)).collect();
+        assert_eq!(
+            out,
+            [false, false, false, true, true, true, true, true, true, true, true, false]
+        );
+        // ties keep the output
+        let mut md = MajorityDebouncer::new(2, true);
+        assert!(md.push(false));
+        assert!(!md.push(false));
+    }
+    #[test]
+    fn integrator() {
+        let mut id = IntegratorDebouncer::new(3, false);
+        let out: Vec<bool> = BOUNCY.iter().map(|x| id.push(*x)).collect();
+        assert_eq!(
+            out,
+            [false, false, false, false, false, false, true, true, true, true, true, true]
+        );
+        assert_eq!(id.level(), 1);
+    }
+    #[test]
+    fn delays() {
+        let mut on = OnDelay::new(3);
+        let mut off = OffDelay::new(3);
+        let on_out: Vec<bool> = BOUNCY.iter().map(|x| on.push(*x)).collect();
+        let off_out: Vec<bool> = BOUNCY.iter().map(|x| off.push(*x)).collect();
+        assert_eq!(
+            on_out,
+            [false, false, false, false, false, false, false, true, false, false, false, false]
+        );
+        assert_eq!(
+            off_out,
+            [true, true, true, true, true, true, true, true, true, true, true, true]
+        );
+        assert!(off.push(false));
+        assert!(!off.push(false));
+        assert!(!off.output());
+    }
+    #[test]
+    fn schmitt() {
+        let mut st = SchmittTrigger::new(1, 0.0, 0.4, 0.6);
+        let input = [0.5, 0.65, 0.5, 0.45, 0.3, 0.5, 0.7];
+        let out: Vec<bool> = input.iter().map(|x| st.push(*x)).collect();
+        assert_eq!(out, [false, true, true, true, false, false, true]);
+        // averaging filters a spike
+        let mut st = SchmittTrigger::new(4, 0.0, 0.4, 0.6);
+        assert!(!st.push(2.0));
+        assert!(!st.push(0.0));
+        assert!((st.mean() - 0.5).abs() < 1e-12);
+        // the sum doesn't keep the rounding errors of huge samples
+        let mut st = SchmittTrigger::new(1, 0.0, 0.4, 0.6);
+        st.push(1e20);
+        assert!(!st.push(0.3));
+        assert_eq!(st.mean(), 0.3);
+        let mut st = SchmittTrigger::new(4, 0.0, 0.4, 0.6);
+        assert!(st.push(1e20));
+        for _ in 0..3 {
+            assert!(st.push(0.3));
+        }
+        assert!(!st.push(0.3));
+        assert!((st.mean() - 0.3).abs() < 1e-15);
+        assert!(!st.push(0.5));
+        assert!((st.mean() - 0.35).abs() < 1e-15);
+    }
+}
+
+/// Output of the majority of the last samples, that keeps its value on ties.
+#[derive(Debug, Clone)]
+pub struct MajorityDebouncer {
+    window: BitWindow,
+    output: bool,
+}
+
+impl MajorityDebouncer {
+    /// Creates a debouncer that votes among the last `max_items` samples, all
+    /// of them starting as `init`.
+    pub fn new(max_items: usize, init: bool) -> MajorityDebouncer {
+        MajorityDebouncer {
+            window: BitWindow::new(max_items, init),
+            output: init,
+        }
+    }
+    /// Push a sample, returning the output.
+    pub fn push(&mut self, input: bool) -> bool {
+        self.window.push(input);
+        let ones = 2 * self.window.count_ones();
+        let n = self.window.capacity();
+        if ones != n {
+            self.output = ones > n;
+        }
+        self.output
+    }
+    /// Returns the output.
+    pub fn output(&self) -> bool {
+        self.output
+    }
+    /// Returns the window of samples.
+    pub fn window(&self) -> &BitWindow {
+        &self.window
+    }
+}
+
+/// A counter that goes up with `true` samples and down with `false` ones,
+/// between `0` and a maximum. The output goes `true` at the maximum and `false`
+/// at `0`, so a bounce only delays it.
+#[derive(Debug, Clone)]
+pub struct IntegratorDebouncer {
+    level: usize,
+    max: usize,
+    output: bool,
+}
+
+impl IntegratorDebouncer {
+    /// Creates a debouncer with a counter from `0` to `max`, at least 1,
+    /// starting at the end of `init`.
+    pub fn new(max: usize, init: bool) -> IntegratorDebouncer {
+        let max = max.max(1);
+        IntegratorDebouncer {
+            level: if init { max } else { 0 },
+            max,
+            output: init,
+        }
+    }
+    /// Push a sample, returning the output.
+    pub fn push(&mut self, input: bool) -> bool {
+        if input {
+            self.level = (self.level + 1).min(self.max);
+        } else {
+            self.level = self.level.saturating_sub(1);
+        }
+        if self.level == self.max {
+            self.output = true;
+        } else if self.level == 0 {
+            self.output = false;
+        }
+        self.output
+    }
+    /// Returns the output.
+    pub fn output(&self) -> bool {
+        self.output
+    }
+    /// Returns the counter.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+}
+
+/// On-delay timer, whose output is `true` once the input has been `true` for
+/// `delay` samples in a row, and `false` as soon as it isn't.
+#[derive(Debug, Clone)]
+pub struct OnDelay {
+    window: BitWindow,
+}
+
+impl OnDelay {
+    /// Creates a timer of `delay` samples, at least 1, counting the one where
+    /// the input changes. The output starts as `false`.
+    pub fn new(delay: usize) -> OnDelay {
+        OnDelay {
+            window: BitWindow::new(delay, false),
+        }
+    }
+    /// Push a sample, returning the output.
+    pub fn push(&mut self, input: bool) -> bool {
+        self.window.push(input);
+        self.output()
+    }
+    /// Returns the output.
+    pub fn output(&self) -> bool {
+        self.window.count_zeros() == 0
+    }
+}
+
+/// Off-delay timer, whose output is `true` as soon as the input is, and
+/// `false` once the input has been `false` for `delay` samples in a row.
+#[derive(Debug, Clone)]
+pub struct OffDelay {
+    window: BitWindow,
+}
+
+impl OffDelay {
+    /// Creates a timer of `delay` samples, at least 1, counting the one where
+    /// the input changes. The output starts as `false`.
+    pub fn new(delay: usize) -> OffDelay {
+        OffDelay {
+            window: BitWindow::new(delay, false),
+        }
+    }
+    /// Push a sample, returning the output.
+    pub fn push(&mut self, input: bool) -> bool {
+        self.window.push(input);
+        self.output()
+    }
+    /// Returns the output.
+    pub fn output(&self) -> bool {
+        self.window.count_ones() > 0
+    }
+}
+
+/// Comparator with hysteresis of the mean of a window of analog samples. The
+/// output goes `true` when the mean goes over the high threshold, and `false`
+/// when it goes under the low one. With a capacity of 1 it compares the
+/// samples themselves.
+///
+/// The sum of the window is compensated (Neumaier), so that small samples
+/// aren't lost next to a huge one and come back when it is evicted. It is also
+/// computed again from the window every `max_items` pushes.
+#[derive(Debug, Clone)]
+pub struct SchmittTrigger {
+    window: SlidingWindow<f64>,
+    sum: f64,
+    /// What the rounding of `sum` lost.
+    compensation: f64,
+    /// Pushes since the sum was computed from the window.
+    pushes: usize,
+    low: f64,
+    high: f64,
+    output: bool,
+}
+
+impl SchmittTrigger {
+    /// Same as [`SlidingWindow::new`], with the `low` and `high` thresholds.
+    /// The output starts as whether `init` is over the middle of them.
+    pub fn new(max_items: usize, init: f64, low: f64, high: f64) -> SchmittTrigger {
+        let window = SlidingWindow::new(max_items, init);
+        SchmittTrigger {
+            sum: init * window.capacity() as f64,
+            compensation: 0.0,
+            pushes: 0,
+            window,
+            low,
+            high,
+            output: init > (low + high) / 2.0,
+        }
+    }
+    /// Push a sample, returning the output.
+    pub fn push(&mut self, x: f64) -> bool {
+        let evicted = self.window.push_pop(x);
+        self.pushes += 1;
+        if self.pushes == self.window.capacity() {
+            self.sum = 0.0;
+            self.compensation = 0.0;
+            for x in self.window.iter() {
+                neumaier_add(&mut self.sum, &mut self.compensation, *x);
+            }
+            self.pushes = 0;
+        } else {
+            neumaier_add(&mut self.sum, &mut self.compensation, x);
+            neumaier_add(&mut self.sum, &mut self.compensation, -evicted);
+        }
+        let mean = self.mean();
+        if mean > self.high {
+            self.output = true;
+        } else if mean < self.low {
+            self.output = false;
+        }
+        self.output
+    }
+    /// Returns the output.
+    pub fn output(&self) -> bool {
+        self.output
+    }
+    /// Returns the mean of the window, which is compared.
+    pub fn mean(&self) -> f64 {
+        (self.sum + self.compensation) / self.window.capacity() as f64
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
+
+/// Adds `x` to `sum`, keeping in `compensation` what the rounding loses.
+fn neumaier_add(sum: &mut f64, compensation: &mut f64, x: f64) {
+    let t = *sum + x;
+    *compensation += if sum.abs() >= x.abs() {
+        (*sum - t) + x
+    } else {
+        (x - t) + *sum
+    };
+    *sum = t;
+}
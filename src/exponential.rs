@@ -0,0 +1,152 @@
+//! Approximate count of events, or sum of small integers, over windows too big
+//! to be kept in memory, with exponential histograms (Datar, Gionis, Indyk and
+//! Motwani).
+//!
+//! Instead of the items, the window keeps buckets of consecutive events, whose
+//! sizes are powers of two, with the time of the newest event in each one.
+//! Only the oldest bucket can be partly out of the window, which bounds the
+//! error, with O(log² n / ε) memory, instead of O(n).
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod tests {
+    use super::ExponentialHistogram;
+    use crate::SlidingWindow;
+    fn event(t: u64) -> bool {
+        // bursts of activity
+        !(t / 1000).is_multiple_of(3) && (t * 2654435761) % 7 < 3
+    }
+    #[test]
+    fn within_error() {
+        for epsilon in [0.5, 0.1, 0.01] {
+            let mut eh = ExponentialHistogram::new(2000, epsilon);
+            let mut exact = SlidingWindow::new(2000, false);
+            for t in 0..20_000 {
+                eh.push(event(t));
+                exact.push(event(t));
+                if t % 250 == 0 {
+                    let count = exact.iter().filter(|e| **e).count() as f64;
+                    let estimate = eh.count() as f64;
+                    assert!((estimate - count).abs() <= epsilon * count, "{epsilon} {t}");
+                    let (low, high) = eh.bounds();
+                    assert!(low as f64 <= count && count <= high as f64);
+                }
+            }
+        }
+    }
+    #[test]
+    fn small_memory() {
+        let mut eh = ExponentialHistogram::new(86_400_000, 0.05);
+        for t in 0..1_000_000 {
+            eh.push(t % 2 == 0);
+        }
+        assert_eq!(eh.bounds().1, 500_000);
+        assert!(eh.buckets() < 250);
+    }
+    #[test]
+    fn sums_and_expires() {
+        let mut eh = ExponentialHistogram::new(3, 0.1);
+        eh.push_value(5);
+        eh.push_value(2);
+        assert_eq!(eh.bounds(), (6, 7));
+        eh.push_value(0);
+        eh.push_value(0);
+        assert_eq!(eh.count(), 2);
+        eh.push_value(0);
+        eh.push_value(0);
+        assert_eq!(eh.count(), 0);
+        assert_eq!(eh.buckets(), 0);
+    }
+}
+
+/// An approximate count of the events among the last `max_items` pushes,
+/// with a relative error of at most `epsilon`.
+#[derive(Debug, Clone)]
+pub struct ExponentialHistogram {
+    capacity: u64,
+    /// Buckets allowed of each size.
+    per_size: usize,
+    /// Pushes so far, the time of the newest one.
+    now: u64,
+    /// Time of the newest event and size of every bucket, newest first.
+    buckets: VecDeque<(u64, u64)>,
+    total: u64,
+}
+
+impl ExponentialHistogram {
+    /// Creates an empty window of `max_items` pushes, at least 1, whose count
+    /// is off by at most `epsilon`, between `0` and `1`, times the real count.
+    pub fn new(max_items: usize, epsilon: f64) -> ExponentialHistogram {
+        let epsilon = epsilon.clamp(f64::EPSILON, 1.0);
+        ExponentialHistogram {
+            capacity: max_items.max(1) as u64,
+            per_size: (1.0 / (2.0 * epsilon)).ceil() as usize + 1,
+            now: 0,
+            buckets: VecDeque::new(),
+            total: 0,
+        }
+    }
+    /// Push whether there was an event, forgetting the oldest push.
+    pub fn push(&mut self, event: bool) {
+        self.push_value(event as u64);
+    }
+    /// Push an amount of events that happened at once, forgetting the oldest
+    /// push. This operation is O(value) amortized, so it is meant for small
+    /// values.
+    pub fn push_value(&mut self, value: u64) {
+        self.now += 1;
+        while let Some((time, size)) = self.buckets.back() {
+            if time + self.capacity > self.now {
+                break;
+            }
+            self.total -= size;
+            self.buckets.pop_back();
+        }
+        for _ in 0..value {
+            self.buckets.push_front((self.now, 1));
+            self.total += 1;
+            self.merge();
+        }
+    }
+    /// Merges the two oldest buckets of every size with too many of them.
+    fn merge(&mut self) {
+        let (mut start, mut size) = (0, 1);
+        loop {
+            let end = start
+                + self
+                    .buckets
+                    .range(start..)
+                    .take_while(|(_, s)| *s == size)
+                    .count();
+            if end - start <= self.per_size {
+                return;
+            }
+            // the merged bucket ends with the newest of the two
+            self.buckets.remove(end - 1);
+            self.buckets[end - 2].1 = 2 * size;
+            start = end - 2;
+            size *= 2;
+        }
+    }
+    /// Returns the estimated amount of events in the window, assuming half of
+    /// the oldest bucket is still in.
+    pub fn count(&self) -> u64 {
+        let (low, high) = self.bounds();
+        (low + high).div_ceil(2)
+    }
+    /// Returns the lowest and highest possible amount of events in the window.
+    pub fn bounds(&self) -> (u64, u64) {
+        match self.buckets.back() {
+            Some((_, size)) => (self.total - size + 1, self.total),
+            None => (0, 0),
+        }
+    }
+    /// Returns the amount of pushes that the window spans.
+    pub fn capacity(&self) -> usize {
+        self.capacity as usize
+    }
+    /// Returns the amount of buckets in memory.
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+}
@@ -0,0 +1,238 @@
+//! Histogram of the contents of a [`SlidingWindow`], whose bin counts are
+//! updated with every push, and the distribution that can be read from it.
+use crate::{panics, SlidingWindow};
+
+#[cfg(test)]
+mod tests {
+    use super::{Bins, HistogramWindow};
+    #[test]
+    fn counts_follow_the_window() {
+        let mut hw = HistogramWindow::new(4, 0.5, Bins::Uniform(0.0, 4.0, 4));
+        assert_eq!(hw.counts(), [4, 0, 0, 0]);
+        for x in [1.5, 2.5, 2.7, 3.0, 4.0] {
+            hw.push(x);
+        }
+        assert_eq!(hw.counts(), [0, 0, 2, 2]);
+        hw.push(-1.0);
+        hw.push(9.0);
+        assert_eq!(hw.counts(), [0, 0, 0, 2]);
+        assert_eq!((hw.underflow(), hw.overflow()), (1, 1));
+        assert_eq!(hw.bin(4.0), Some(3));
+        assert_eq!(hw.bin(0.0), Some(0));
+    }
+    #[test]
+    fn distribution() {
+        let mut hw = HistogramWindow::new(100, 0.0, Bins::Edges(vec![0.0, 1.0, 3.0, 10.0]));
+        for t in 0..100 {
+            hw.push(t as f64 / 10.0);
+        }
+        assert_eq!(hw.counts(), [10, 20, 70]);
+        let density = hw.density();
+        let area: f64 = density.iter().zip(hw.widths()).map(|(d, w)| d * w).sum();
+        assert!((area - 1.0).abs() < 1e-12);
+        assert_eq!(hw.cdf(), [0.1, 0.3, 1.0]);
+        assert!((hw.quantile(0.5) - 5.0).abs() < 1e-9);
+        assert_eq!(hw.quantile(0.0), 0.0);
+        assert_eq!(hw.quantile(1.0), 10.0);
+    }
+    #[test]
+    fn adapt() {
+        let mut hw = HistogramWindow::new(10, 0.0, Bins::Uniform(0.0, 1.0, 2));
+        for t in 0..10 {
+            hw.push(10.0 + t as f64);
+        }
+        assert_eq!(hw.overflow(), 10);
+        hw.adapt(3);
+        assert_eq!(hw.edges(), [10.0, 13.0, 16.0, 19.0]);
+        assert_eq!(hw.counts(), [3, 3, 4]);
+        hw.rebin(Bins::Uniform(0.0, 20.0, 1));
+        assert_eq!(hw.counts(), [10]);
+    }
+    #[test]
+    #[should_panic(expected = "bin edges must be at least two and increasing")]
+    fn bad_edges() {
+        HistogramWindow::new(10, 0.0, Bins::Edges(vec![0.0, 2.0, 1.0]));
+    }
+}
+
+/// How the range of the histogram is split in bins. Every bin includes its
+/// lower edge, and the last one its upper edge too.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bins {
+    /// The given amount of bins of the same width from the first value to the
+    /// second.
+    Uniform(f64, f64, usize),
+    /// The edges of the bins, increasing.
+    Edges(Vec<f64>),
+}
+
+impl Bins {
+    fn edges(self) -> Vec<f64> {
+        let edges = match self {
+            Bins::Uniform(min, max, bins) => {
+                let width = (max - min) / bins as f64;
+                (0..=bins).map(|i| min + i as f64 * width).collect()
+            }
+            Bins::Edges(edges) => edges,
+        };
+        if edges.len() < 2 || edges.windows(2).any(|w| w[0] >= w[1]) {
+            panic!("{}", panics::BAD_EDGES);
+        }
+        edges
+    }
+}
+
+/// A [`SlidingWindow`] with the histogram of its contents, updated in
+/// O(log bins) per push. Samples outside the edges are counted apart.
+#[derive(Debug, Clone)]
+pub struct HistogramWindow {
+    window: SlidingWindow<f64>,
+    edges: Vec<f64>,
+    counts: Vec<usize>,
+    underflow: usize,
+    overflow: usize,
+}
+
+impl HistogramWindow {
+    /// Same as [`SlidingWindow::new`], with the histogram split in `bins`.
+    ///
+    /// # Panics
+    ///
+    /// If there aren't at least two edges, or they aren't increasing.
+    pub fn new(max_items: usize, init: f64, bins: Bins) -> HistogramWindow {
+        let mut hw = HistogramWindow {
+            window: SlidingWindow::new(max_items, init),
+            edges: Vec::new(),
+            counts: Vec::new(),
+            underflow: 0,
+            overflow: 0,
+        };
+        hw.rebin(bins);
+        hw
+    }
+    /// Returns the bin of `x`, or `None` if it's outside the edges.
+    pub fn bin(&self, x: f64) -> Option<usize> {
+        let last = self.edges.len() - 1;
+        if x < self.edges[0] || x > self.edges[last] || x.is_nan() {
+            return None;
+        }
+        Some((self.edges.partition_point(|e| *e <= x) - 1).min(last - 1))
+    }
+    fn count(&mut self, x: f64, add: bool) {
+        let counter = match self.bin(x) {
+            Some(bin) => &mut self.counts[bin],
+            None if x > self.edges[0] => &mut self.overflow,
+            None => &mut self.underflow,
+        };
+        if add {
+            *counter += 1;
+        } else {
+            *counter -= 1;
+        }
+    }
+    /// Push an element to the window, forgetting the oldest, and updates the
+    /// counts.
+    pub fn push(&mut self, x: f64) {
+        let evicted = self.window.push_pop(x);
+        self.count(evicted, false);
+        self.count(x, true);
+    }
+    /// Replaces the bins and counts the window again. This operation is
+    /// O(n log bins).
+    ///
+    /// # Panics
+    ///
+    /// Same as [`HistogramWindow::new`].
+    pub fn rebin(&mut self, bins: Bins) {
+        self.edges = bins.edges();
+        self.counts = vec![0; self.edges.len() - 1];
+        self.underflow = 0;
+        self.overflow = 0;
+        for i in 0..self.window.capacity() {
+            self.count(self.window[i], true);
+        }
+    }
+    /// Replaces the bins with `bins` uniform ones from the minimum to the
+    /// maximum of the window, and counts it again. This operation is
+    /// O(n log bins).
+    pub fn adapt(&mut self, bins: usize) {
+        let min = self.window.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self
+            .window
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let (min, max) = if min < max {
+            (min, max)
+        } else {
+            (min - 0.5, max + 0.5)
+        };
+        self.rebin(Bins::Uniform(min, max, bins.max(1)));
+    }
+    /// Returns the edges of the bins.
+    pub fn edges(&self) -> &[f64] {
+        &self.edges
+    }
+    /// Returns the width of every bin.
+    pub fn widths(&self) -> Vec<f64> {
+        self.edges.windows(2).map(|w| w[1] - w[0]).collect()
+    }
+    /// Returns the amount of samples in every bin.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+    /// Returns the amount of samples below the first edge.
+    pub fn underflow(&self) -> usize {
+        self.underflow
+    }
+    /// Returns the amount of samples above the last edge.
+    pub fn overflow(&self) -> usize {
+        self.overflow
+    }
+    /// Returns the density of every bin, the fraction of the window in it over
+    /// its width.
+    pub fn density(&self) -> Vec<f64> {
+        let n = self.window.capacity() as f64;
+        self.counts
+            .iter()
+            .zip(self.widths())
+            .map(|(c, w)| *c as f64 / (n * w))
+            .collect()
+    }
+    /// Returns, for every bin, the fraction of the window up to its upper
+    /// edge, including the samples below the first edge.
+    pub fn cdf(&self) -> Vec<f64> {
+        let n = self.window.capacity() as f64;
+        let mut below = self.underflow;
+        self.counts
+            .iter()
+            .map(|c| {
+                below += c;
+                below as f64 / n
+            })
+            .collect()
+    }
+    /// Returns an approximation of the `q` quantile, interpolating linearly
+    /// inside its bin. Quantiles below or above the edges are clamped to
+    /// them.
+    pub fn quantile(&self, q: f64) -> f64 {
+        let target = q.clamp(0.0, 1.0) * self.window.capacity() as f64;
+        let mut below = self.underflow as f64;
+        if target <= below {
+            return self.edges[0];
+        }
+        for (i, c) in self.counts.iter().enumerate() {
+            let c = *c as f64;
+            if target <= below + c {
+                let fraction = (target - below) / c;
+                return self.edges[i] + fraction * (self.edges[i + 1] - self.edges[i]);
+            }
+            below += c;
+        }
+        self.edges[self.edges.len() - 1]
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
@@ -0,0 +1,106 @@
+//! A [`SlidingWindow`] that reports when a hop boundary is reached, for
+//! hopping and tumbling window computations.
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::HoppingWindow;
+    #[test]
+    fn tumbling() {
+        let mut hw = HoppingWindow::tumbling(3, 0);
+        assert!(hw.push(1).is_none());
+        assert!(hw.push(2).is_none());
+        assert_eq!(*hw.push(3).unwrap(), [3, 2, 1]);
+        assert!(hw.push(4).is_none());
+        assert_eq!(hw.pending(), 1);
+        assert!(hw.push(5).is_none());
+        assert_eq!(*hw.push(6).unwrap(), [6, 5, 4]);
+        assert_eq!(hw.pending(), 0);
+    }
+    #[test]
+    fn hopping() {
+        let mut hw = HoppingWindow::new(4, 0, 2);
+        let emitted: Vec<Vec<i32>> = (1..=6)
+            .filter_map(|i| hw.push(i).map(|w| w.to_vec()))
+            .collect();
+        assert_eq!(
+            emitted,
+            [vec![2, 1, 0, 0], vec![4, 3, 2, 1], vec![6, 5, 4, 3]]
+        );
+    }
+    #[test]
+    fn push_with() {
+        let mut hw = HoppingWindow::new(2, 0, 0);
+        assert_eq!(hw.hop(), 1);
+        assert_eq!(hw.push_with(3, |w| w.iter().sum::<i32>()), Some(3));
+        assert_eq!(hw.push_with(4, |w| w.iter().sum::<i32>()), Some(7));
+    }
+}
+
+/// A [`SlidingWindow`] that counts pushes and hands out the window once every
+/// `hop` of them. It's a tumbling window when `hop` equals the capacity.
+#[derive(Debug)]
+pub struct HoppingWindow<T>
+where
+    T: Clone,
+{
+    window: SlidingWindow<T>,
+    hop: usize,
+    pushed: usize,
+}
+
+impl<T> HoppingWindow<T>
+where
+    T: Clone,
+{
+    /// Same as [`SlidingWindow::new`], plus the amount of pushes between
+    /// boundaries. A `hop` of 0 is treated as 1.
+    pub fn new(max_items: usize, init: T, hop: usize) -> HoppingWindow<T> {
+        HoppingWindow {
+            window: SlidingWindow::new(max_items, init),
+            hop: hop.max(1),
+            pushed: 0,
+        }
+    }
+    /// Creates a window where the hop is its capacity, so every element is
+    /// handed out exactly once.
+    pub fn tumbling(max_items: usize, init: T) -> HoppingWindow<T> {
+        HoppingWindow::new(max_items, init, max_items)
+    }
+    /// Push an element to the window, returning it if this push reached a hop
+    /// boundary.
+    pub fn push(&mut self, a: T) -> Option<&SlidingWindow<T>> {
+        self.window.push(a);
+        self.pushed += 1;
+        if self.pushed >= self.hop {
+            self.pushed = 0;
+            Some(&self.window)
+        } else {
+            None
+        }
+    }
+    /// Push an element to the window and, if a hop boundary was reached, call
+    /// `f` on it.
+    pub fn push_with<R, F>(&mut self, a: T, f: F) -> Option<R>
+    where
+        F: FnOnce(&SlidingWindow<T>) -> R,
+    {
+        self.push(a).map(f)
+    }
+    /// Returns the amount of pushes between boundaries.
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+    /// Returns the amount of pushes since the last boundary.
+    pub fn pending(&self) -> usize {
+        self.pushed
+    }
+    /// Returns the underlying window, regardless of the boundaries.
+    pub fn window(&self) -> &SlidingWindow<T> {
+        &self.window
+    }
+    /// Consumes the wrapper, returning the underlying window.
+    pub fn into_window(self) -> SlidingWindow<T> {
+        self.window
+    }
+}
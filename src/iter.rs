@@ -0,0 +1,148 @@
+//! Adaptors that feed an [`Iterator`] into a [`SlidingWindow`], one item at a
+//! time, reusing the same window for the whole iteration.
+use crate::SlidingWindow;
+use std::fmt;
+
+#[cfg(test)]
+mod tests {
+    use super::SlidingIterExt;
+    #[test]
+    fn sliding() {
+        let mut it = (1..=4).sliding(3, 0);
+        assert_eq!(*it.next_window().unwrap(), [1, 0, 0]);
+        assert_eq!(*it.next_window().unwrap(), [2, 1, 0]);
+        assert_eq!(*it.next_window().unwrap(), [3, 2, 1]);
+        assert_eq!(*it.next_window().unwrap(), [4, 3, 2]);
+        assert!(it.next_window().is_none());
+        assert_eq!(*it.window(), [4, 3, 2]);
+    }
+    #[test]
+    fn sliding_map() {
+        let avg: Vec<f64> = [2.0, 4.0, 6.0, 8.0]
+            .into_iter()
+            .sliding_map(2, 0.0, |w| w.iter().sum::<f64>() / 2.0)
+            .collect();
+        assert_eq!(avg, [1.0, 3.0, 5.0, 7.0]);
+    }
+    #[test]
+    fn sliding_map_len() {
+        let it = (0..10).sliding_map(4, 0, |w| w[3]);
+        assert_eq!(it.len(), 10);
+        assert!(format!("{it:?}").starts_with("SlidingMap { inner: Sliding {"));
+        assert_eq!(it.last(), Some(6));
+    }
+}
+
+/// Extension methods for any [`Iterator`] whose items can be stored in a
+/// [`SlidingWindow`].
+pub trait SlidingIterExt: Iterator + Sized
+where
+    Self::Item: Clone,
+{
+    /// Pushes every item into a window of `capacity` elements initialized to
+    /// `init`. As the window is borrowed, it isn't an [`Iterator`], use
+    /// [`Sliding::next_window`] to advance it.
+    fn sliding(self, capacity: usize, init: Self::Item) -> Sliding<Self> {
+        Sliding {
+            iter: self,
+            window: SlidingWindow::new(capacity, init),
+        }
+    }
+    /// Yields the result of calling `f` on the window after every item has
+    /// been pushed to it.
+    fn sliding_map<F, R>(self, capacity: usize, init: Self::Item, f: F) -> SlidingMap<Self, F>
+    where
+        F: FnMut(&SlidingWindow<Self::Item>) -> R,
+    {
+        SlidingMap {
+            inner: self.sliding(capacity, init),
+            f,
+        }
+    }
+}
+
+impl<I> SlidingIterExt for I
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+}
+
+/// Returned by [`SlidingIterExt::sliding`].
+#[derive(Debug)]
+pub struct Sliding<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    iter: I,
+    window: SlidingWindow<I::Item>,
+}
+
+impl<I> Sliding<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    /// Pushes the next item and returns the window, or `None` when the inner
+    /// iterator is exhausted.
+    pub fn next_window(&mut self) -> Option<&SlidingWindow<I::Item>> {
+        let item = self.iter.next()?;
+        self.window.push(item);
+        Some(&self.window)
+    }
+    /// Returns the window as it is after the last item pushed.
+    pub fn window(&self) -> &SlidingWindow<I::Item> {
+        &self.window
+    }
+    /// Consumes the adaptor, returning the window.
+    pub fn into_window(self) -> SlidingWindow<I::Item> {
+        self.window
+    }
+}
+
+/// Returned by [`SlidingIterExt::sliding_map`].
+pub struct SlidingMap<I, F>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    inner: Sliding<I>,
+    f: F,
+}
+
+/// Same as a derived `Debug`, but without requiring it for the function.
+impl<I, F> fmt::Debug for SlidingMap<I, F>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlidingMap")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, F, R> Iterator for SlidingMap<I, F>
+where
+    I: Iterator,
+    I::Item: Clone,
+    F: FnMut(&SlidingWindow<I::Item>) -> R,
+{
+    type Item = R;
+    fn next(&mut self) -> Option<R> {
+        self.inner.next_window().map(&mut self.f)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.iter.size_hint()
+    }
+}
+
+impl<I, F, R> ExactSizeIterator for SlidingMap<I, F>
+where
+    I: ExactSizeIterator,
+    I::Item: Clone,
+    F: FnMut(&SlidingWindow<I::Item>) -> R,
+{
+}
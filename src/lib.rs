@@ -8,10 +8,79 @@ use std::ops::Index;
 use std::slice::{Iter, IterMut};
 use std::vec::IntoIter;
 
+pub mod adaptive;
+pub mod aggregate;
+pub mod bits;
+pub mod changepoint;
+pub mod correlation;
+pub mod counting;
+pub mod debounce;
+pub mod exponential;
+pub mod histogram;
+pub mod hopping;
+pub mod iter;
+pub mod outlier;
+pub mod periodicity;
+pub mod recorder;
+pub mod regression;
+pub mod rls;
+pub mod sampling;
+pub mod savgol;
+pub mod sdft;
+pub mod segment_tree;
+pub mod spc;
+pub mod spectrum;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod trigger;
+pub mod weighted;
+
 /// Consts for panics in the crate
 mod panics {
     /// Used in the Froms to panic when you try to start a zero width buffer
     pub const START_EMPTY: &str = "can't operate on empty SlidingWindow";
+    /// Used in the AggregateWindow when an inverse that worked stops working
+    pub const INCONSISTENT_INVERSE: &str = "Aggregate::inverse has to be consistent";
+    /// Used in range queries when the range goes beyond the capacity
+    pub const OUT_OF_RANGE: &str = "range out of the window";
+    /// Used in the FFT when the data doesn't have the planned length
+    pub const FFT_LENGTH: &str = "data must match the FFT length";
+    /// Used when weighting a window with a kernel of another length
+    pub const KERNEL_LENGTH: &str = "the kernel must match the capacity of the window";
+    /// Used when operating on windows that must be the same size
+    pub const CAPACITY_MISMATCH: &str = "windows must have the same capacity";
+    /// Used when fitting a polynomial with fewer points than coefficients
+    pub const ORDER_TOO_HIGH: &str = "the polynomial order must be lower than the capacity";
+    /// Used in the control charts, whose constants are only tabulated up to 10
+    pub const SUBGROUP_SIZE: &str = "subgroups must have between 1 and 10 samples";
+    /// Used in the control charts started from a window too short to estimate
+    pub const TOO_FEW_SUBGROUPS: &str = "the window must hold at least two subgroups";
+    /// Used in the histograms when the edges of the bins make no sense
+    pub const BAD_EDGES: &str = "bin edges must be at least two and increasing";
+}
+
+/// Fraction of the largest spread since running moments were computed from a
+/// window, under which the spread left is mostly rounding error.
+const CANCELLATION: f64 = 1e-6;
+
+/// Deterministic, roughly white, input in [-1, 1) for the tests.
+#[cfg(test)]
+fn test_input(t: u64) -> f64 {
+    use sampling::{Rng, SplitMix64};
+    2.0 * SplitMix64::new(t).next_f64() - 1.0
+}
+
+/// Small noise in [-0.2, 0.2], that repeats every 7 samples, for the tests.
+#[cfg(test)]
+fn test_noise(t: usize) -> f64 {
+    [0.1, -0.2, 0.15, -0.05, 0.0, 0.2, -0.1][t % 7]
+}
+
+/// Whether both slices have the same length and their items are within
+/// `1e-12`, for the tests.
+#[cfg(test)]
+fn test_close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-12)
 }
 
 #[cfg(test)]
@@ -35,7 +104,7 @@ mod tests {
     }
     #[test]
     fn from_slice() {
-        let a = vec![5, 5, 5];
+        let a = [5, 5, 5];
         let st = SlidingWindow::from(&a[..]);
         assert_eq!(st, &a[..]);
     }
@@ -52,7 +121,7 @@ mod tests {
     }
     #[test]
     fn into_vec() {
-        let a = vec![1, 1, 2];
+        let a = [1, 1, 2];
         let st: SlidingWindow<u8> = a[..].into();
         assert_eq!(st, &a[..])
     }
@@ -85,6 +154,15 @@ mod tests {
         assert_eq!(st, [4, 2, 3]);
     }
     #[test]
+    fn push_pop() {
+        let mut st = SlidingWindow::from([10, 15, 16]);
+        assert_eq!(st.push_pop(5), 16);
+        assert_eq!(st.push_pop(6), 15);
+        assert_eq!(st.push_pop(7), 10);
+        assert_eq!(st.push_pop(8), 5);
+        assert_eq!(st, [8, 7, 6]);
+    }
+    #[test]
     fn push_slice_too_much() {
         let mut st = SlidingWindow::new(5, 6);
         let a = [1; 6];
@@ -109,7 +187,7 @@ mod tests {
     fn use_iter_no_modif() {
         let a = [1, 2, 3, 4];
         let mut st = SlidingWindow::from(a);
-        st.iter().map(|x| x * x).count();
+        st.iter().map(|x| x * x).for_each(drop);
         assert!(st.iter().zip(a).all(|(x, y)| { *x == y }));
         st.iter_mut().map(|x| *x *= *x).count();
         st.iter().zip(a).for_each(|(x, y)| {
@@ -160,7 +238,7 @@ mod tests {
         let a = [5; 4];
         let st = SlidingWindow::new(4, 5);
         assert_eq!(st, a);
-        let a = vec![1, 5, 6, 7, -8];
+        let a = [1, 5, 6, 7, -8];
         let st = SlidingWindow::from(&a[..]);
         assert_eq!(st, &a[..]);
     }
@@ -195,7 +273,7 @@ mod tests {
 }
 
 /// A structure that holds the last N items pushed to it.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SlidingWindow<T>
 where
     T: Clone,
@@ -229,6 +307,16 @@ where
             self.current_insert = 0;
         }
     }
+    /// Push an element to the window, returning the oldest, which is the one
+    /// forgotten.
+    pub fn push_pop(&mut self, a: T) -> T {
+        let old = std::mem::replace(&mut self.vec[self.capacity - 1 - self.current_insert], a);
+        self.current_insert += 1;
+        if self.current_insert >= self.capacity {
+            self.current_insert = 0;
+        }
+        old
+    }
     /// Push a slice, where the newest item is at index 0.
     pub fn push_slice(&mut self, a: &[T]) {
         a.iter().rev().for_each(|a| {
@@ -243,13 +331,13 @@ where
     /// Returns an ordered iterator, where the first element is the newest and
     /// the last, the oldest.
     #[inline]
-    pub fn iter(&self) -> Chain<Iter<T>, Iter<T>> {
+    pub fn iter(&self) -> Chain<Iter<'_, T>, Iter<'_, T>> {
         // it doesn't rely on as_vec, bcs this way is lazier
         let (a, b) = self.vec.split_at(self.capacity - self.current_insert);
         b.iter().chain(a.iter())
     }
     /// Returns a mutable iterator in the same order as the `iter` method.
-    pub fn iter_mut(&mut self) -> Chain<IterMut<T>, IterMut<T>> {
+    pub fn iter_mut(&mut self) -> Chain<IterMut<'_, T>, IterMut<'_, T>> {
         let (a, b) = self.vec.split_at_mut(self.capacity - self.current_insert);
         b.iter_mut().chain(a.iter_mut())
     }
@@ -0,0 +1,247 @@
+//! Detection of outliers against the contents of a [`SlidingWindow`], which
+//! can be flagged, clipped or replaced before they enter the window.
+//!
+//! The median absolute deviation is `0` as soon as more than half of the window
+//! holds the same value, which is usual with quantized readings. The median
+//! based detectors then fall back to the mean absolute deviation scaled by
+//! `1.2533`, as Iglewicz and Hoaglin suggest.
+//!
+//! Until the window is full of pushed samples, or when it is constant, there
+//! is no spread to compare against, so nothing is considered an outlier. Keep
+//! in mind that a window that clips or replaces outliers may never follow a
+//! genuine step in the signal.
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Detector, OutlierFilter};
+    use crate::test_noise as noisy;
+    use crate::SlidingWindow;
+    #[test]
+    fn median_and_mad() {
+        let st = SlidingWindow::from([1.0, 9.0, 2.0, 4.0, 2.0, 6.0]);
+        assert_eq!(st.median(), 3.0);
+        assert_eq!(st.median_abs_deviation(), 1.5);
+        assert_eq!(SlidingWindow::from([3.0, 1.0, 2.0]).median(), 2.0);
+    }
+    #[test]
+    fn replaces_spikes() {
+        for detector in [
+            Detector::ZScore { threshold: 3.0 },
+            Detector::ModifiedZScore { threshold: 3.5 },
+            Detector::Hampel { threshold: 3.0 },
+        ] {
+            let mut of = OutlierFilter::new(15, 10.0, detector, Action::Replace);
+            for t in 0..60 {
+                assert!(!of.push(10.0 + noisy(t)).outlier, "{detector:?}");
+            }
+            let verdict = of.push(100.0);
+            assert!(verdict.outlier && verdict.score > 3.0, "{detector:?}");
+            assert!((verdict.stored - 10.0).abs() < 0.2, "{detector:?}");
+            assert_eq!(of.window()[0], verdict.stored);
+        }
+    }
+    #[test]
+    fn clips_and_flags() {
+        let detector = Detector::Hampel { threshold: 2.0 };
+        let mut clip = OutlierFilter::new(5, 0.0, detector, Action::Clip);
+        let mut flag = OutlierFilter::new(5, 0.0, detector, Action::Flag);
+        for x in [1.0, -1.0, 1.0, -1.0, 0.0] {
+            clip.push(x);
+            flag.push(x);
+        }
+        // median 0, MAD 1
+        let bound = 2.0 * 1.4826;
+        let verdict = clip.push(-50.0);
+        assert!(verdict.outlier);
+        assert!((verdict.stored + bound).abs() < 1e-12);
+        let verdict = flag.push(-50.0);
+        assert!(verdict.outlier && verdict.stored == -50.0);
+        assert!(!clip.classify(1.0).outlier);
+    }
+    #[test]
+    fn quantized_window() {
+        let readings = [10.0, 11.0, 10.0, 10.0, 11.0, 10.0, 9.0, 10.0, 11.0, 10.0];
+        for detector in [
+            Detector::ModifiedZScore { threshold: 3.5 },
+            Detector::Hampel { threshold: 3.0 },
+        ] {
+            let mut of = OutlierFilter::new(10, 10.0, detector, Action::Replace);
+            for x in readings.iter().cycle().take(30) {
+                assert!(!of.push(*x).outlier, "{detector:?}");
+            }
+            assert_eq!(of.window().median_abs_deviation(), 0.0);
+            let verdict = of.push(1000.0);
+            assert!(verdict.outlier, "{detector:?}");
+            assert_eq!(verdict.stored, 10.0);
+        }
+    }
+    #[test]
+    fn constant_window() {
+        let mut of =
+            OutlierFilter::new(4, 1.0, Detector::ZScore { threshold: 3.0 }, Action::Replace);
+        let verdict = of.push(5.0);
+        assert!(!verdict.outlier && verdict.score == 0.0);
+        assert_eq!(of.window()[0], 5.0);
+    }
+}
+
+/// How far a sample may be from the center of the window before it is an
+/// outlier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Detector {
+    /// Distance from the mean, in standard deviations. Simple, but the
+    /// outliers already in the window inflate the deviation.
+    ZScore {
+        /// Usually `3`.
+        threshold: f64,
+    },
+    /// Iglewicz and Hoaglin's modified z-score, `0.6745` times the distance
+    /// from the median over the median absolute deviation.
+    ModifiedZScore {
+        /// Usually `3.5`.
+        threshold: f64,
+    },
+    /// Hampel identifier, distance from the median in units of the MAD scaled
+    /// by `1.4826`, which estimates the standard deviation of normal data.
+    Hampel {
+        /// Usually `3`.
+        threshold: f64,
+    },
+}
+
+/// What happens to an outlier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// It enters the window as is, it is only reported.
+    Flag,
+    /// It enters the window clamped to the threshold.
+    Clip,
+    /// The center of the window, mean or median, enters instead.
+    Replace,
+}
+
+/// The result of checking a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Verdict {
+    /// Distance of the sample from the center, in the units of the threshold.
+    pub score: f64,
+    /// Whether the score is above the threshold.
+    pub outlier: bool,
+    /// The value that enters, or would enter, the window.
+    pub stored: f64,
+}
+
+impl SlidingWindow<f64> {
+    /// Returns the median of the window, the mean of the two central items if
+    /// the capacity is even. This operation is O(n log n).
+    pub fn median(&self) -> f64 {
+        median(self.to_vec())
+    }
+    /// Returns the median of the absolute deviations from the median. This
+    /// operation is O(n log n).
+    pub fn median_abs_deviation(&self) -> f64 {
+        let m = self.median();
+        median(self.iter().map(|x| (x - m).abs()).collect())
+    }
+}
+
+fn median(mut v: Vec<f64>) -> f64 {
+    v.sort_by(f64::total_cmp);
+    let n = v.len();
+    if n % 2 == 1 {
+        v[n / 2]
+    } else {
+        (v[n / 2 - 1] + v[n / 2]) / 2.0
+    }
+}
+
+/// The MAD around `center` times `scale` or, if it is `0`, the mean absolute
+/// deviation times `1.2533`. Both estimate the standard deviation of normal
+/// data.
+fn robust_spread(window: &SlidingWindow<f64>, center: f64, scale: f64) -> f64 {
+    let deviations: Vec<f64> = window.iter().map(|x| (x - center).abs()).collect();
+    let n = deviations.len() as f64;
+    let mean_abs_deviation = deviations.iter().sum::<f64>() / n;
+    let mad = median(deviations);
+    if mad > 0.0 {
+        scale * mad
+    } else {
+        1.2533 * mean_abs_deviation
+    }
+}
+
+/// A [`SlidingWindow`] that checks every sample against its contents before
+/// pushing it.
+#[derive(Debug, Clone)]
+pub struct OutlierFilter {
+    window: SlidingWindow<f64>,
+    detector: Detector,
+    action: Action,
+    /// Samples pushed, up to the capacity.
+    pushed: usize,
+}
+
+impl OutlierFilter {
+    /// Same as [`SlidingWindow::new`], with the `detector` and the `action` for
+    /// outliers.
+    pub fn new(max_items: usize, init: f64, detector: Detector, action: Action) -> OutlierFilter {
+        OutlierFilter {
+            window: SlidingWindow::new(max_items, init),
+            detector,
+            action,
+            pushed: 0,
+        }
+    }
+    /// Checks `x` against the window, without pushing it.
+    pub fn classify(&self, x: f64) -> Verdict {
+        // center, spread in units of the score and threshold
+        let (center, spread, threshold) = match self.detector {
+            Detector::ZScore { threshold } => {
+                let n = self.window.capacity() as f64;
+                let mean = self.window.iter().sum::<f64>() / n;
+                let var = self.window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+                (mean, var.sqrt(), threshold)
+            }
+            Detector::ModifiedZScore { threshold } => {
+                let m = self.window.median();
+                (m, robust_spread(&self.window, m, 1.0 / 0.6745), threshold)
+            }
+            Detector::Hampel { threshold } => {
+                let m = self.window.median();
+                (m, robust_spread(&self.window, m, 1.4826), threshold)
+            }
+        };
+        if spread <= 0.0 {
+            return Verdict {
+                score: 0.0,
+                outlier: false,
+                stored: x,
+            };
+        }
+        let score = (x - center).abs() / spread;
+        let outlier = self.pushed == self.window.capacity() && score > threshold;
+        let stored = match self.action {
+            _ if !outlier => x,
+            Action::Flag => x,
+            Action::Clip => x.clamp(center - threshold * spread, center + threshold * spread),
+            Action::Replace => center,
+        };
+        Verdict {
+            score,
+            outlier,
+            stored,
+        }
+    }
+    /// Checks `x` against the window and pushes it, or what replaces it.
+    pub fn push(&mut self, x: f64) -> Verdict {
+        let verdict = self.classify(x);
+        self.window.push(verdict.stored);
+        self.pushed = (self.pushed + 1).min(self.window.capacity());
+        verdict
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
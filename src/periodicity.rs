@@ -0,0 +1,153 @@
+//! Autocorrelation and estimation of the period of the contents of a
+//! [`SlidingWindow`], for instance to find oscillating control loops.
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::{acf_period, yin_period};
+    use crate::SlidingWindow;
+    use std::f64::consts::PI;
+    fn oscillating(period: f64) -> SlidingWindow<f64> {
+        let mut st = SlidingWindow::new(500, 0.0);
+        for t in 0..500 {
+            let t = t as f64;
+            st.push((2.0 * PI * t / period).sin() + 0.3 * (4.0 * PI * t / period).cos() + 4.0);
+        }
+        st
+    }
+    #[test]
+    fn autocorrelation() {
+        let st = SlidingWindow::from([1.0, -1.0, 1.0, -1.0]);
+        let acf = st.autocorrelation(10).unwrap();
+        assert_eq!(acf, [1.0, -0.75, 0.5, -0.25]);
+        assert!(SlidingWindow::new(4, 2.0).autocorrelation(2).is_none());
+    }
+    #[test]
+    fn acf() {
+        let p = acf_period(&oscillating(25.0), 100).unwrap();
+        assert!((p.samples - 25.0).abs() < 0.1, "{p:?}");
+        assert!(p.confidence > 0.9);
+        let p = acf_period(&oscillating(31.4), 100).unwrap();
+        assert!((p.samples - 31.4).abs() < 0.2, "{p:?}");
+        assert!(acf_period(&SlidingWindow::new(100, 1.0), 50).is_none());
+    }
+    #[test]
+    fn yin() {
+        let p = yin_period(&oscillating(25.0), 100, 0.1).unwrap();
+        assert!((p.samples - 25.0).abs() < 0.1, "{p:?}");
+        assert!(p.confidence > 0.9);
+        let p = yin_period(&oscillating(31.4), 100, 0.1).unwrap();
+        assert!((p.samples - 31.4).abs() < 0.1, "{p:?}");
+        assert!(yin_period(&SlidingWindow::new(100, 1.0), 50, 0.1).is_none());
+    }
+}
+
+/// The estimated period of a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Period {
+    /// Length of the period, in samples. It's interpolated, so it needn't be
+    /// a whole number.
+    pub samples: f64,
+    /// How periodic the window is, from `0`, not at all, to `1`, perfectly.
+    pub confidence: f64,
+}
+
+impl SlidingWindow<f64> {
+    /// Returns the normalized autocorrelation of the window for lags from `0`
+    /// up to `max_lag`, or the capacity minus one, whichever is lower. Index
+    /// `0` is always `1`. Returns `None` if the window is constant.
+    pub fn autocorrelation(&self, max_lag: usize) -> Option<Vec<f64>> {
+        let n = self.capacity();
+        let mean = self.iter().sum::<f64>() / n as f64;
+        let x: Vec<f64> = self.iter().rev().map(|x| x - mean).collect();
+        let energy: f64 = x.iter().map(|x| x * x).sum();
+        if energy <= 0.0 {
+            return None;
+        }
+        let acf = (0..=max_lag.min(n - 1))
+            .map(|k| x.iter().zip(&x[k..]).map(|(a, b)| a * b).sum::<f64>() / energy)
+            .collect();
+        Some(acf)
+    }
+}
+
+/// Vertex of the parabola through `y[i - 1]`, `y[i]` and `y[i + 1]`, as the
+/// offset from `i` and its value.
+fn parabolic(y: &[f64], i: usize) -> (f64, f64) {
+    if i == 0 || i + 1 >= y.len() {
+        return (0.0, y[i]);
+    }
+    let (a, b, c) = (y[i - 1], y[i], y[i + 1]);
+    let den = a - 2.0 * b + c;
+    if den == 0.0 {
+        return (0.0, b);
+    }
+    let offset = 0.5 * (a - c) / den;
+    (offset, b - 0.25 * (a - c) * offset)
+}
+
+/// Estimates the period by picking the highest peak of the autocorrelation,
+/// after it first goes below zero, with periods up to `max_period`. The
+/// confidence is the autocorrelation at the peak. Returns `None` if there is no
+/// positive peak.
+pub fn acf_period(window: &SlidingWindow<f64>, max_period: usize) -> Option<Period> {
+    let acf = window.autocorrelation(max_period + 1)?;
+    let start = acf.iter().position(|r| *r < 0.0)?;
+    let (peak, _) = acf[..acf.len() - 1]
+        .iter()
+        .enumerate()
+        .skip(start)
+        .filter(|(k, r)| **r > 0.0 && **r >= acf[k - 1] && **r >= acf[k + 1])
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    let (offset, value) = parabolic(&acf, peak);
+    Some(Period {
+        samples: peak as f64 + offset,
+        confidence: value.clamp(0.0, 1.0),
+    })
+}
+
+/// Estimates the period with the YIN algorithm, with periods up to
+/// `max_period`, which has to be below half the capacity to be reliable. The
+/// period is the first dip of the normalized difference below `threshold`,
+/// usually `0.1` to `0.2`, or the deepest one if none is. The confidence is one
+/// minus the normalized difference at the period. Returns `None` if the window
+/// is too small or constant.
+pub fn yin_period(
+    window: &SlidingWindow<f64>,
+    max_period: usize,
+    threshold: f64,
+) -> Option<Period> {
+    let x: Vec<f64> = window.iter().rev().copied().collect();
+    let max_lag = max_period.min(x.len() / 2);
+    if max_lag < 2 {
+        return None;
+    }
+    let w = x.len() - max_lag;
+    let diff: Vec<f64> = (0..=max_lag)
+        .map(|tau| (0..w).map(|j| (x[j] - x[j + tau]).powi(2)).sum())
+        .collect();
+    // cumulative mean normalized difference
+    let mut cmnd = vec![1.0; max_lag + 1];
+    let mut running = 0.0;
+    for tau in 1..=max_lag {
+        running += diff[tau];
+        if running <= 0.0 {
+            return None;
+        }
+        cmnd[tau] = diff[tau] * tau as f64 / running;
+    }
+    let tau = match (2..max_lag).find(|&t| cmnd[t] < threshold) {
+        Some(mut t) => {
+            while t + 1 < max_lag && cmnd[t + 1] < cmnd[t] {
+                t += 1;
+            }
+            t
+        }
+        None => (2..max_lag).min_by(|a, b| cmnd[*a].total_cmp(&cmnd[*b]))?,
+    };
+    let (offset, value) = parabolic(&cmnd, tau);
+    Some(Period {
+        samples: tau as f64 + offset,
+        confidence: (1.0 - value).clamp(0.0, 1.0),
+    })
+}
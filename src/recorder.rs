@@ -0,0 +1,153 @@
+//! A flight recorder, that keeps the last records of a process in a
+//! [`SlidingWindow`] to write them out when something goes wrong, even when
+//! it panics.
+use crate::SlidingWindow;
+use std::fmt::Debug;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::{Duration, Instant};
+
+/// How long the panic hook waits for another thread to release the records.
+const HOOK_LOCK_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[cfg(test)]
+mod tests {
+    use super::FlightRecorder;
+    #[test]
+    fn dump() {
+        let fr = FlightRecorder::new(3);
+        let mut out = Vec::new();
+        fr.dump(&mut out).unwrap();
+        assert!(out.is_empty());
+        for x in ["start", "run", "stop", "error"] {
+            fr.record(x);
+        }
+        fr.dump(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "#1 \"run\"\n#2 \"stop\"\n#3 \"error\"\n"
+        );
+        assert_eq!(fr.records(), [(1, "run"), (2, "stop"), (3, "error")]);
+        assert_eq!(fr.sequence(), 4);
+    }
+}
+
+/// The records and the amount of them ever pushed.
+#[derive(Debug)]
+struct Records<T: Clone> {
+    window: SlidingWindow<Option<(u64, T)>>,
+    sequence: u64,
+}
+
+/// Keeps the last records pushed, with their sequence number.
+///
+/// Clones share the records, so one can be given to every thread that records.
+/// The records are behind a mutex, as the panic hook needs them too.
+#[derive(Debug)]
+pub struct FlightRecorder<T: Clone + Debug> {
+    records: Arc<Mutex<Records<T>>>,
+}
+
+impl<T: Clone + Debug> Clone for FlightRecorder<T> {
+    fn clone(&self) -> Self {
+        FlightRecorder {
+            records: Arc::clone(&self.records),
+        }
+    }
+}
+
+impl<T: Clone + Debug> FlightRecorder<T> {
+    /// Creates a recorder of the last `max_items` records, which starts empty.
+    pub fn new(max_items: usize) -> FlightRecorder<T> {
+        FlightRecorder {
+            records: Arc::new(Mutex::new(Records {
+                window: SlidingWindow::new(max_items, None),
+                sequence: 0,
+            })),
+        }
+    }
+    /// Locks the records, even if a thread panicked while holding them, as
+    /// they are still worth reading.
+    fn lock(&self) -> MutexGuard<'_, Records<T>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Push a record, forgetting the oldest.
+    pub fn record(&self, a: T) {
+        let mut records = self.lock();
+        let sequence = records.sequence;
+        records.window.push(Some((sequence, a)));
+        records.sequence += 1;
+    }
+    /// Returns the records kept with their sequence number, from the oldest.
+    pub fn records(&self) -> Vec<(u64, T)> {
+        let records = self.lock();
+        records.window.iter().rev().flatten().cloned().collect()
+    }
+    /// Returns the amount of records ever pushed, which is the sequence number
+    /// of the next one.
+    pub fn sequence(&self) -> u64 {
+        self.lock().sequence
+    }
+    /// Writes the records kept to `out`, from the oldest, one per line, as
+    /// `#<sequence> <record:?>`.
+    pub fn dump<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        write_records(&self.lock(), out)
+    }
+}
+
+fn write_records<T: Clone + Debug, W: io::Write>(
+    records: &Records<T>,
+    out: &mut W,
+) -> io::Result<()> {
+    for (sequence, record) in records.window.iter().rev().flatten() {
+        writeln!(out, "#{sequence} {record:?}")?;
+    }
+    Ok(())
+}
+
+impl<T: Clone + Debug + Send + 'static> FlightRecorder<T> {
+    /// Same as `install_panic_hook_to` with the standard error.
+    pub fn install_panic_hook(&self) {
+        self.install_panic_hook_to(io::stderr());
+    }
+    /// Adds a panic hook that, after the one that was set, dumps the records to
+    /// `out`, after a line with how many there are. The hook is global, and it
+    /// stays after the recorder is dropped.
+    ///
+    /// If another thread is recording when the hook runs, the hook waits for
+    /// it, up to 100 ms. If they stay locked, for instance because the panic
+    /// happened while recording in the same thread, they aren't written, as
+    /// waiting longer would deadlock.
+    pub fn install_panic_hook_to<W: io::Write + Send + 'static>(&self, out: W) {
+        let records = Arc::clone(&self.records);
+        let out = Mutex::new(out);
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            let mut out = out.lock().unwrap_or_else(PoisonError::into_inner);
+            let deadline = Instant::now() + HOOK_LOCK_TIMEOUT;
+            let records = loop {
+                match records.try_lock() {
+                    Ok(records) => break records,
+                    Err(TryLockError::Poisoned(e)) => break e.into_inner(),
+                    Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                        std::thread::yield_now();
+                    }
+                    Err(TryLockError::WouldBlock) => {
+                        let _ = writeln!(out, "flight recorder locked, records lost");
+                        return;
+                    }
+                }
+            };
+            let kept = records.window.iter().flatten().count();
+            // errors can't be handled while panicking
+            let _ = writeln!(
+                out,
+                "flight recorder, last {kept} of {} records:",
+                records.sequence
+            );
+            let _ = write_records(&records, &mut *out);
+            let _ = out.flush();
+        }));
+    }
+}
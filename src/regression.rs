@@ -0,0 +1,144 @@
+//! Least squares line over the contents of a [`SlidingWindow`], kept up to
+//! date in O(1) per push, to follow trends and extrapolate them.
+use crate::correlation::PairedWindows;
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::RegressionWindow;
+    #[test]
+    fn exact_line() {
+        let mut rw = RegressionWindow::new(10, 0.0);
+        for t in 1..=25 {
+            rw.push(2.0 * t as f64 + 3.0);
+        }
+        assert!((rw.slope() - 2.0).abs() < 1e-9);
+        assert!((rw.intercept() - 3.0).abs() < 1e-9);
+        assert!((rw.level() - 53.0).abs() < 1e-9);
+        assert!((rw.predict(5.0) - 63.0).abs() < 1e-9);
+        assert!((rw.r_squared() - 1.0).abs() < 1e-12);
+        assert!((rw.time_to(73.0).unwrap() - 10.0).abs() < 1e-9);
+        assert!(rw.time_to(0.0).is_none());
+    }
+    /// Slope, intercept and r² of the least squares line over the windows.
+    fn batch_fit(rw: &RegressionWindow) -> (f64, f64, f64) {
+        let x = rw.x().to_vec();
+        let y = rw.window().to_vec();
+        let n = x.len() as f64;
+        let (mx, my) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+        let sxy: f64 = x.iter().zip(&y).map(|(a, b)| (a - mx) * (b - my)).sum();
+        let sxx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+        let syy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+        (sxy / sxx, my - sxy / sxx * mx, sxy * sxy / (sxx * syy))
+    }
+    #[test]
+    fn matches_batch_fit() {
+        let mut rw = RegressionWindow::new(20, 0.0);
+        for t in 0..200 {
+            let y = ((t * 7919) % 101) as f64 / 10.0 - 0.05 * t as f64;
+            rw.push_at(0.5 * t as f64, y);
+        }
+        let (slope, intercept, r_squared) = batch_fit(&rw);
+        assert!((rw.slope() - slope).abs() < 1e-9);
+        assert!((rw.intercept() - intercept).abs() < 1e-6);
+        assert!((rw.r_squared() - r_squared).abs() < 1e-9);
+    }
+    #[test]
+    fn long_run() {
+        let mut rw = RegressionWindow::new(50, 0.0);
+        for t in 0..2_000_000u64 {
+            let y = ((t * 7919) % 101) as f64 - 1e-3 * t as f64;
+            rw.push(y);
+        }
+        let (slope, _, r_squared) = batch_fit(&rw);
+        assert!((rw.slope() - slope).abs() < 1e-9);
+        assert!((rw.r_squared() - r_squared).abs() < 1e-9);
+    }
+    #[test]
+    fn flat() {
+        let rw = RegressionWindow::new(5, 4.0);
+        assert_eq!(rw.slope(), 0.0);
+        assert_eq!(rw.level(), 4.0);
+        assert_eq!(rw.r_squared(), 0.0);
+        assert!(rw.time_to(5.0).is_none());
+    }
+}
+
+/// A [`SlidingWindow`] of samples, `y`, with the abscissa of each one, `x`,
+/// that keeps the least squares line that best fits them.
+///
+/// `x` is either the amount of samples pushed, with `push`, or a timestamp,
+/// with `push_at`.
+#[derive(Debug, Clone)]
+pub struct RegressionWindow {
+    pairs: PairedWindows,
+}
+
+impl RegressionWindow {
+    /// Same as [`SlidingWindow::new`]. The initial samples get an `x` from
+    /// `1 - max_items` to `0`.
+    pub fn new(max_items: usize, init: f64) -> RegressionWindow {
+        let y = SlidingWindow::new(max_items, init);
+        let x: SlidingWindow<f64> = (0..y.capacity()).map(|i| -(i as f64)).collect();
+        RegressionWindow {
+            pairs: PairedWindows::new(x, y),
+        }
+    }
+    /// Push a sample, one unit of `x` after the newest.
+    pub fn push(&mut self, y: f64) {
+        self.push_at(self.pairs.x[0] + 1.0, y);
+    }
+    /// Push a sample taken at `x`.
+    pub fn push_at(&mut self, x: f64, y: f64) {
+        self.pairs.push(x, y);
+    }
+    /// Returns the slope of the line, per unit of `x`, or `0` if all the `x`
+    /// are the same.
+    pub fn slope(&self) -> f64 {
+        if self.pairs.moments.m2_x > 0.0 {
+            self.pairs.moments.c_xy / self.pairs.moments.m2_x
+        } else {
+            0.0
+        }
+    }
+    /// Returns the value of the line at `x`.
+    pub fn value_at(&self, x: f64) -> f64 {
+        self.pairs.moments.mean_y + self.slope() * (x - self.pairs.moments.mean_x)
+    }
+    /// Returns the value of the line at `x = 0`.
+    pub fn intercept(&self) -> f64 {
+        self.value_at(0.0)
+    }
+    /// Returns the value of the line at the newest sample.
+    pub fn level(&self) -> f64 {
+        self.value_at(self.pairs.x[0])
+    }
+    /// Returns the value of the line `ahead` units of `x` after the newest
+    /// sample.
+    pub fn predict(&self, ahead: f64) -> f64 {
+        self.value_at(self.pairs.x[0] + ahead)
+    }
+    /// Returns how many units of `x` after the newest sample the line reaches
+    /// `value`, or `None` if it doesn't in the future.
+    pub fn time_to(&self, value: f64) -> Option<f64> {
+        let slope = self.slope();
+        if slope == 0.0 {
+            return None;
+        }
+        let ahead = (value - self.level()) / slope;
+        (ahead >= 0.0).then_some(ahead)
+    }
+    /// Returns the coefficient of determination, from `0`, the line explains
+    /// nothing, to `1`, the samples are on the line.
+    pub fn r_squared(&self) -> f64 {
+        self.pairs.moments.pearson().map_or(0.0, |r| r * r)
+    }
+    /// Returns the window of samples.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.pairs.y
+    }
+    /// Returns the window of `x` of the samples.
+    pub fn x(&self) -> &SlidingWindow<f64> {
+        &self.pairs.x
+    }
+}
@@ -0,0 +1,159 @@
+//! Online identification of ARX models with recursive least squares, from a
+//! window of past outputs and a window of inputs.
+//!
+//! The model is the one in `examples/basic.rs`,
+//! `y(t) = a · [y(t-1), y(t-2), ...] + b · [u(t), u(t-1), ...]`, where both
+//! coefficient vectors are aligned, newest first, with their windows.
+use crate::SlidingWindow;
+
+#[cfg(test)]
+mod tests {
+    use super::Rls;
+    use crate::test_input as input;
+    use crate::SlidingWindow;
+    #[test]
+    fn identifies_basic_example() {
+        let carac_pol = [0.5, -0.4, 0.2, -0.3, 0.05];
+        let mut sys = SlidingWindow::new(5, 0.0);
+        let mut rls = Rls::new(5, 2, 1.0, 1e4);
+        for t in 0..500 {
+            let u = input(t);
+            let y = sys.iter().zip(carac_pol).map(|(y, a)| a * y).sum::<f64>() + 2.0 * u;
+            rls.step(u, y);
+            sys.push(y);
+        }
+        let a = rls.output_coefficients();
+        let b = rls.input_coefficients();
+        assert!(a.iter().zip(carac_pol).all(|(x, y)| (x - y).abs() < 1e-3));
+        assert!((b[0] - 2.0).abs() < 1e-3 && b[1].abs() < 1e-3);
+        let expected = sys.iter().zip(carac_pol).map(|(y, a)| a * y).sum::<f64>() + 2.0 * 0.3;
+        assert!((rls.predict(0.3) - expected).abs() < 1e-2);
+        assert_eq!(rls.covariance().len(), 7);
+    }
+    #[test]
+    fn tracks_drift() {
+        let mut rls = Rls::new(1, 1, 0.95, 1e4);
+        let mut y = 0.0;
+        for t in 0..2000 {
+            let a = if t < 1000 { 0.9 } else { 0.5 };
+            let u = input(t);
+            y = a * y + u;
+            rls.step(u, y);
+        }
+        let p = rls.parameters();
+        assert!(
+            (p[0] - 0.5).abs() < 1e-6 && (p[1] - 1.0).abs() < 1e-6,
+            "{p:?}"
+        );
+    }
+}
+
+/// Recursive least squares estimator of an ARX model, with a forgetting
+/// factor.
+#[derive(Debug, Clone)]
+pub struct Rls {
+    outputs: SlidingWindow<f64>,
+    inputs: SlidingWindow<f64>,
+    /// Output coefficients followed by the input ones.
+    theta: Vec<f64>,
+    covariance: Vec<Vec<f64>>,
+    lambda: f64,
+}
+
+impl Rls {
+    /// Creates an estimator with `na` past outputs and `nb` inputs, at least 1
+    /// each. `lambda`, in `(0, 1]`, is the forgetting factor, lower values
+    /// track changes faster but are noisier. The covariance starts as `delta`
+    /// times the identity, a big `delta`, like `1e4`, means no prior knowledge.
+    pub fn new(na: usize, nb: usize, lambda: f64, delta: f64) -> Rls {
+        let outputs = SlidingWindow::new(na, 0.0);
+        let inputs = SlidingWindow::new(nb, 0.0);
+        let n = outputs.capacity() + inputs.capacity();
+        let covariance = (0..n)
+            .map(|i| (0..n).map(|j| if i == j { delta } else { 0.0 }).collect())
+            .collect();
+        Rls {
+            outputs,
+            inputs,
+            theta: vec![0.0; n],
+            covariance,
+            lambda,
+        }
+    }
+    /// The regressor, past outputs followed by inputs.
+    fn regressor(&self) -> Vec<f64> {
+        self.outputs
+            .iter()
+            .chain(self.inputs.iter())
+            .copied()
+            .collect()
+    }
+    /// Updates the estimate with the `input` applied at this instant and the
+    /// `output` that it produced. Returns the prediction error before updating.
+    pub fn step(&mut self, input: f64, output: f64) -> f64 {
+        self.inputs.push(input);
+        let phi = self.regressor();
+        let error = output - dot(&self.theta, &phi);
+        let p_phi: Vec<f64> = self.covariance.iter().map(|row| dot(row, &phi)).collect();
+        let gain = 1.0 / (self.lambda + dot(&phi, &p_phi));
+        let k: Vec<f64> = p_phi.iter().map(|x| x * gain).collect();
+        self.theta
+            .iter_mut()
+            .zip(&k)
+            .for_each(|(t, k)| *t += k * error);
+        // P is symmetric, so phi^T P is p_phi. Only the upper triangle is
+        // computed and then mirrored, or rounding errors make it diverge.
+        for (i, ki) in k.iter().enumerate() {
+            for (j, pj) in p_phi.iter().enumerate().skip(i) {
+                let p = (self.covariance[i][j] - ki * pj) / self.lambda;
+                self.covariance[i][j] = p;
+                self.covariance[j][i] = p;
+            }
+        }
+        self.outputs.push(output);
+        error
+    }
+    /// Returns the output of the model for the next instant, if `input` is
+    /// applied.
+    pub fn predict(&self, input: f64) -> f64 {
+        let a = dot(self.output_coefficients(), &self.outputs.to_vec());
+        let b = self.input_coefficients();
+        a + b[0] * input
+            + self
+                .inputs
+                .iter()
+                .zip(&b[1..])
+                .map(|(u, b)| u * b)
+                .sum::<f64>()
+    }
+    /// Returns the estimated coefficients of the past outputs, newest first.
+    pub fn output_coefficients(&self) -> &[f64] {
+        &self.theta[..self.outputs.capacity()]
+    }
+    /// Returns the estimated coefficients of the inputs, newest first, where
+    /// index `0` is the current input.
+    pub fn input_coefficients(&self) -> &[f64] {
+        &self.theta[self.outputs.capacity()..]
+    }
+    /// Returns all the estimated coefficients, outputs first.
+    pub fn parameters(&self) -> &[f64] {
+        &self.theta
+    }
+    /// Returns the covariance of the estimate, scaled by the noise variance,
+    /// in the order of `parameters`.
+    pub fn covariance(&self) -> &[Vec<f64>] {
+        &self.covariance
+    }
+    /// Returns the window of past outputs.
+    pub fn outputs(&self) -> &SlidingWindow<f64> {
+        &self.outputs
+    }
+    /// Returns the window of inputs.
+    pub fn inputs(&self) -> &SlidingWindow<f64> {
+        &self.inputs
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
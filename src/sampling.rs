@@ -0,0 +1,178 @@
+//! Uniform random sample of the last items pushed, without keeping them all,
+//! with priority sampling (Babcock, Datar and Motwani).
+//!
+//! Every item gets a random priority and the sample is the items of the window
+//! with the highest ones. Only the items that are beaten by fewer than `k`
+//! newer ones can make it to the sample, which are O(k log(n / k)) on average.
+//!
+//! The randomness comes from an [`Rng`], so any generator can be plugged in,
+//! and the crate stays without dependencies. [`SplitMix64`] is a small
+//! seedable one.
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod tests {
+    use super::{Rng, SlidingSample, SplitMix64};
+    #[test]
+    fn splitmix() {
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220a8397b1dcdaf);
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.next_f64())));
+    }
+    #[test]
+    fn follows_the_window() {
+        let mut ss = SlidingSample::new(10, 3, SplitMix64::new(7));
+        assert!(ss.sample().is_empty());
+        ss.push(0);
+        ss.push(1);
+        assert_eq!(ss.sample(), [&1, &0]);
+        for t in 2..1000 {
+            ss.push(t);
+            let sample = ss.sample();
+            assert_eq!(sample.len(), 3);
+            assert!(sample.iter().all(|x| **x + 10 > t && **x <= t));
+            assert!(sample.windows(2).all(|w| w[0] > w[1]));
+        }
+        assert!(ss.candidates() < 10);
+    }
+    #[test]
+    fn is_uniform() {
+        let mut ss = SlidingSample::new(10, 2, SplitMix64::new(42));
+        let mut hits = [0; 10];
+        for t in 0..50_000 {
+            ss.push(t);
+            if t >= 10 {
+                ss.sample().iter().for_each(|x| hits[t - **x] += 1);
+            }
+        }
+        // every position is sampled with probability 2 / 10
+        let expected = 50_000.0 * 0.2;
+        assert!(hits
+            .iter()
+            .all(|h| (*h as f64 - expected).abs() < 0.05 * expected));
+    }
+    #[test]
+    fn reproducible() {
+        let run = |seed| {
+            let mut ss = SlidingSample::new(100, 5, SplitMix64::new(seed));
+            (0..1000).for_each(|t| ss.push(t));
+            ss.sample().into_iter().copied().collect::<Vec<_>>()
+        };
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+    }
+}
+
+/// A source of random numbers.
+pub trait Rng {
+    /// Returns 64 random bits.
+    fn next_u64(&mut self) -> u64;
+    /// Returns a random number in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Vigna's SplitMix64 generator. Fast and good enough for sampling, but not
+/// cryptographically secure.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Creates a generator from a `seed`. The same seed gives the same
+    /// numbers.
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// An item that could be in the sample.
+#[derive(Debug, Clone)]
+struct Candidate<T> {
+    /// When it was pushed.
+    time: u64,
+    priority: u64,
+    /// Newer items with a higher priority.
+    beaten: usize,
+    item: T,
+}
+
+/// A uniform random sample, without replacement, of `k` of the last
+/// `max_items` pushed.
+#[derive(Debug, Clone)]
+pub struct SlidingSample<T, R: Rng> {
+    capacity: u64,
+    k: usize,
+    rng: R,
+    now: u64,
+    /// Oldest first.
+    candidates: VecDeque<Candidate<T>>,
+}
+
+impl<T, R: Rng> SlidingSample<T, R> {
+    /// Creates an empty sampler of `k` items, at least 1, out of the last
+    /// `max_items`, at least `k`, with random numbers from `rng`.
+    pub fn new(max_items: usize, k: usize, rng: R) -> SlidingSample<T, R> {
+        let k = k.max(1);
+        SlidingSample {
+            capacity: max_items.max(k) as u64,
+            k,
+            rng,
+            now: 0,
+            candidates: VecDeque::new(),
+        }
+    }
+    /// Push an item, forgetting the oldest.
+    pub fn push(&mut self, a: T) {
+        self.now += 1;
+        while let Some(c) = self.candidates.front() {
+            if c.time + self.capacity > self.now {
+                break;
+            }
+            self.candidates.pop_front();
+        }
+        let priority = self.rng.next_u64();
+        let k = self.k;
+        self.candidates.retain_mut(|c| {
+            if c.priority < priority {
+                c.beaten += 1;
+            }
+            c.beaten < k
+        });
+        self.candidates.push_back(Candidate {
+            time: self.now,
+            priority,
+            beaten: 0,
+            item: a,
+        });
+    }
+    /// Returns the sample, newest first. It has `k` items, unless fewer were
+    /// pushed.
+    pub fn sample(&self) -> Vec<&T> {
+        let mut top: Vec<&Candidate<T>> = self.candidates.iter().collect();
+        top.sort_unstable_by_key(|c| std::cmp::Reverse(c.priority));
+        top.truncate(self.k);
+        top.sort_unstable_by_key(|c| std::cmp::Reverse(c.time));
+        top.into_iter().map(|c| &c.item).collect()
+    }
+    /// Returns the amount of items kept, that could be in the sample.
+    pub fn candidates(&self) -> usize {
+        self.candidates.len()
+    }
+    /// Returns the amount of items the sample is taken from.
+    pub fn capacity(&self) -> usize {
+        self.capacity as usize
+    }
+}
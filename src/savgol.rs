@@ -0,0 +1,189 @@
+//! Savitzky–Golay smoothing and differentiation over a [`SlidingWindow`].
+//!
+//! The window is fitted by least squares with a polynomial, which is then
+//! evaluated, or derived, at the newest or the central sample. As the fit only
+//! depends on the positions of the samples, it reduces to a fixed set of
+//! coefficients per output, computed once.
+use crate::{panics, SlidingWindow};
+
+#[cfg(test)]
+mod tests {
+    use super::{Point, SavitzkyGolay};
+    use crate::test_close as close;
+    #[test]
+    fn classic_coefficients() {
+        let sg = SavitzkyGolay::new(5, 0.0, 2, Point::Center);
+        let smooth = [-3.0, 12.0, 17.0, 12.0, -3.0].map(|c| c / 35.0);
+        assert!(close(sg.coefficients(0).unwrap(), &smooth));
+        // newest first, so the newest sample gets the positive weight
+        let deriv = [2.0, 1.0, 0.0, -1.0, -2.0].map(|c| c / 10.0);
+        assert!(close(sg.coefficients(1).unwrap(), &deriv));
+        let second = [2.0, -1.0, -2.0, -1.0, 2.0].map(|c| c / 7.0);
+        assert!(close(sg.coefficients(2).unwrap(), &second));
+    }
+    #[test]
+    fn causal_is_exact_on_polynomials() {
+        let f = |t: f64| 3.0 * t * t - 2.0 * t + 1.0;
+        let mut sg = SavitzkyGolay::new(9, 0.0, 3, Point::Newest);
+        for t in 0..20 {
+            sg.push(f(t as f64));
+        }
+        assert!((sg.value() - f(19.0)).abs() < 1e-7);
+        assert!((sg.derivative() - (6.0 * 19.0 - 2.0)).abs() < 1e-7);
+        assert!((sg.second_derivative() - 6.0).abs() < 1e-7);
+    }
+    #[test]
+    fn smooths_noise() {
+        let mut sg = SavitzkyGolay::new(21, 0.0, 2, Point::Center);
+        for t in 0..100 {
+            let noise = if t % 2 == 0 { 0.5 } else { -0.5 };
+            sg.push(0.1 * t as f64 + noise);
+        }
+        assert!((sg.derivative() - 0.1).abs() < 0.01);
+        assert!((sg.value() - 8.9).abs() < 0.05);
+        assert!(sg.coefficients(3).is_none());
+    }
+    #[test]
+    #[should_panic(expected = "the polynomial order must be lower than the capacity")]
+    fn order_too_high() {
+        SavitzkyGolay::new(3, 0.0, 3, Point::Center);
+    }
+}
+
+/// The sample at which the fitted polynomial is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Point {
+    /// The newest one, so the output is causal and without delay, but noisier.
+    Newest,
+    /// The central one, which is delayed half the window, but smoother.
+    Center,
+}
+
+/// A Savitzky–Golay filter, that owns the window it filters.
+///
+/// Derivatives are per sample, divide them by the sample period, or its
+/// square, to get them per unit of time.
+#[derive(Debug, Clone)]
+pub struct SavitzkyGolay {
+    window: SlidingWindow<f64>,
+    /// For each derivative up to the order, its coefficients, newest first.
+    coefficients: Vec<Vec<f64>>,
+}
+
+impl SavitzkyGolay {
+    /// Same as [`SlidingWindow::new`], fitted with a polynomial of degree
+    /// `order`, evaluated at `point`.
+    ///
+    /// # Panics
+    ///
+    /// If `order` isn't lower than the capacity.
+    pub fn new(max_items: usize, init: f64, order: usize, point: Point) -> SavitzkyGolay {
+        let window = SlidingWindow::new(max_items, init);
+        let n = window.capacity();
+        if order >= n {
+            panic!("{}", panics::ORDER_TOO_HIGH);
+        }
+        let m = order + 1;
+        let center = match point {
+            Point::Newest => 0.0,
+            Point::Center => (n - 1) as f64 / 2.0,
+        };
+        // positions relative to the evaluated sample, scaled for stability
+        let scale = ((n - 1) as f64 / 2.0).max(1.0);
+        let t: Vec<f64> = (0..n).map(|i| (center - i as f64) / scale).collect();
+        let powers: Vec<Vec<f64>> = t
+            .iter()
+            .map(|t| (0..m).map(|j| t.powi(j as i32)).collect())
+            .collect();
+        let mut normal = vec![vec![0.0; m]; m];
+        for row in &powers {
+            for j in 0..m {
+                for k in 0..m {
+                    normal[j][k] += row[j] * row[k];
+                }
+            }
+        }
+        let inverse = invert(normal);
+        // row d of inverse * A^T gives the d-th coefficient of the polynomial
+        let mut factorial = 1.0;
+        let coefficients = (0..m)
+            .map(|d| {
+                if d > 1 {
+                    factorial *= d as f64;
+                }
+                let k = factorial / scale.powi(d as i32);
+                powers
+                    .iter()
+                    .map(|row| k * (0..m).map(|j| inverse[d][j] * row[j]).sum::<f64>())
+                    .collect()
+            })
+            .collect();
+        SavitzkyGolay {
+            window,
+            coefficients,
+        }
+    }
+    /// Push an element to the window, forgetting the oldest.
+    pub fn push(&mut self, a: f64) {
+        self.window.push(a);
+    }
+    /// Returns the coefficients, newest first, for the `derivative`th
+    /// derivative, where `0` is the smoothed value, or `None` if the
+    /// derivative is higher than the order, as it is always zero.
+    pub fn coefficients(&self, derivative: usize) -> Option<&[f64]> {
+        self.coefficients.get(derivative).map(Vec::as_slice)
+    }
+    /// Applies the coefficients of a derivative to the window.
+    fn apply(&self, derivative: usize) -> f64 {
+        self.coefficients
+            .get(derivative)
+            .map_or(0.0, |c| self.window.iter().zip(c).map(|(x, c)| x * c).sum())
+    }
+    /// Returns the smoothed value.
+    pub fn value(&self) -> f64 {
+        self.apply(0)
+    }
+    /// Returns the first derivative, per sample.
+    pub fn derivative(&self) -> f64 {
+        self.apply(1)
+    }
+    /// Returns the second derivative, per sample squared.
+    pub fn second_derivative(&self) -> f64 {
+        self.apply(2)
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
+
+/// Inverts a small symmetric positive definite matrix with Gauss–Jordan
+/// elimination and partial pivoting.
+fn invert(mut a: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let m = a.len();
+    let mut inv: Vec<Vec<f64>> = (0..m)
+        .map(|i| (0..m).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    for col in 0..m {
+        let pivot = (col..m)
+            .max_by(|x, y| a[*x][col].abs().total_cmp(&a[*y][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+        let p = a[col][col];
+        for j in 0..m {
+            a[col][j] /= p;
+            inv[col][j] /= p;
+        }
+        for row in 0..m {
+            if row != col {
+                let f = a[row][col];
+                for j in 0..m {
+                    a[row][j] -= f * a[col][j];
+                    inv[row][j] -= f * inv[col][j];
+                }
+            }
+        }
+    }
+    inv
+}
@@ -0,0 +1,183 @@
+//! Tracking of a few frequencies of a [`SlidingWindow`], without transforming
+//! the whole window on every push.
+use crate::spectrum::Complex;
+use crate::SlidingWindow;
+use std::f64::consts::PI;
+
+#[cfg(test)]
+mod tests {
+    use super::{SlidingDft, ToneDetector};
+    use crate::spectrum::Fft;
+    use crate::SlidingWindow;
+    use std::f64::consts::PI;
+    fn mains(t: usize) -> f64 {
+        let t = t as f64 / 1000.0;
+        230.0 * (2.0 * PI * 50.0 * t).sin() + 20.0 * (2.0 * PI * 150.0 * t + 0.3).sin() + 1.5
+    }
+    #[test]
+    fn matches_fft() {
+        let mut sdft = SlidingDft::new(100, 0.0, &[0, 5, 15, 20]);
+        for t in 0..537 {
+            sdft.push(mains(t));
+        }
+        let fft = Fft::new(100).spectrum(sdft.window(), 1000.0);
+        for (i, k) in [0, 5, 15, 20].into_iter().enumerate() {
+            assert!((sdft.bin(i) - fft.bins()[k]).norm() < 1e-6);
+        }
+        let amps = sdft.amplitudes();
+        assert!((amps[0] - 1.5).abs() < 1e-9);
+        assert!((amps[1] - 230.0).abs() < 1e-9);
+        assert!((amps[2] - 20.0).abs() < 1e-9);
+        assert!(amps[3].abs() < 1e-9);
+        assert_eq!(sdft.frequency(1, 1000.0), 50.0);
+    }
+    #[test]
+    fn damped() {
+        let mut sdft = SlidingDft::with_damping(100, 0.0, &[5], 0.99999);
+        for t in 0..10_000 {
+            sdft.push(mains(t));
+        }
+        assert!((sdft.amplitudes()[0] - 230.0).abs() < 0.5);
+    }
+    #[test]
+    fn tone_detector() {
+        let mut st = SlidingWindow::new(200, 0.0);
+        for t in 0..200 {
+            st.push(mains(t));
+        }
+        let d50 = ToneDetector::new(50.0, 1000.0, 100.0);
+        let d60 = ToneDetector::new(60.0, 1000.0, 100.0);
+        assert!((d50.amplitude(&st) - 230.0).abs() < 1e-6);
+        assert!(d50.detect(&st));
+        assert!(!d60.detect(&st));
+    }
+}
+
+/// A [`SlidingWindow`] that keeps some bins of its DFT up to date in O(1) per
+/// push and bin, using the element that the push evicts.
+///
+/// The bins are the same as the ones of [`crate::spectrum`], but rounding
+/// errors accumulate over time. Damping, with a factor `r` slightly lower than
+/// 1, keeps them bounded at the cost of a small bias.
+#[derive(Debug, Clone)]
+pub struct SlidingDft {
+    window: SlidingWindow<f64>,
+    bins: Vec<usize>,
+    /// `r e^(2 pi i k / N)` for every bin
+    twiddles: Vec<Complex>,
+    values: Vec<Complex>,
+    /// `r^N`, applied to the evicted element
+    r_n: f64,
+}
+
+impl SlidingDft {
+    /// Same as [`SlidingWindow::new`], plus the indexes of the bins to track,
+    /// where bin `k` has a frequency of `k * sample_rate / max_items`. Without
+    /// damping.
+    pub fn new(max_items: usize, init: f64, bins: &[usize]) -> SlidingDft {
+        SlidingDft::with_damping(max_items, init, bins, 1.0)
+    }
+    /// Same as `new`, with a damping factor `r`, like `0.9999`.
+    pub fn with_damping(max_items: usize, init: f64, bins: &[usize], r: f64) -> SlidingDft {
+        let window = SlidingWindow::new(max_items, init);
+        let n = window.capacity() as f64;
+        let twiddles = bins
+            .iter()
+            .map(|&k| Complex::from_polar(r, 2.0 * PI * k as f64 / n))
+            .collect();
+        let values = bins
+            .iter()
+            .map(|&k| {
+                window
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .fold(Complex::default(), |acc, (t, x)| {
+                        let ang = -2.0 * PI * (k * t) as f64 / n;
+                        acc + Complex::from_polar(*x, ang)
+                    })
+            })
+            .collect();
+        SlidingDft {
+            r_n: r.powi(window.capacity() as i32),
+            window,
+            bins: bins.to_vec(),
+            twiddles,
+            values,
+        }
+    }
+    /// Push an element to the window, updating the bins.
+    pub fn push(&mut self, a: f64) {
+        let delta = a - self.r_n * self.window.push_pop(a);
+        self.values
+            .iter_mut()
+            .zip(&self.twiddles)
+            .for_each(|(v, w)| *v = (*v + Complex::new(delta, 0.0)) * *w);
+    }
+    /// Returns the value of the `i`th tracked bin.
+    pub fn bin(&self, i: usize) -> Complex {
+        self.values[i]
+    }
+    /// Returns the value of every tracked bin.
+    pub fn values(&self) -> &[Complex] {
+        &self.values
+    }
+    /// Returns the frequency of the `i`th tracked bin.
+    pub fn frequency(&self, i: usize, sample_rate: f64) -> f64 {
+        self.bins[i] as f64 * sample_rate / self.window.capacity() as f64
+    }
+    /// Returns the amplitude of every tracked bin, scaled like
+    /// [`crate::spectrum::Spectrum::amplitudes`].
+    pub fn amplitudes(&self) -> Vec<f64> {
+        let n = self.window.capacity();
+        self.bins
+            .iter()
+            .zip(&self.values)
+            .map(|(&k, v)| {
+                if k % n == 0 || 2 * (k % n) == n {
+                    v.norm() / n as f64
+                } else {
+                    2.0 * v.norm() / n as f64
+                }
+            })
+            .collect()
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
+
+/// Detects a tone in a window with the Goertzel algorithm, which is cheaper
+/// than a full transform when looking for a single frequency.
+#[derive(Debug, Clone, Copy)]
+pub struct ToneDetector {
+    omega: f64,
+    threshold: f64,
+}
+
+impl ToneDetector {
+    /// Looks for `frequency`, in a signal sampled at `sample_rate`, with an
+    /// amplitude of at least `threshold`.
+    pub fn new(frequency: f64, sample_rate: f64, threshold: f64) -> ToneDetector {
+        ToneDetector {
+            omega: 2.0 * PI * frequency / sample_rate,
+            threshold,
+        }
+    }
+    /// Returns the amplitude of the tone in the window. It's exact when the
+    /// window holds a whole number of periods.
+    pub fn amplitude(&self, window: &SlidingWindow<f64>) -> f64 {
+        let coeff = 2.0 * self.omega.cos();
+        let (s1, s2) = window
+            .iter()
+            .rev()
+            .fold((0.0, 0.0), |(s1, s2), x| (x + coeff * s1 - s2, s1));
+        let power = s1 * s1 + s2 * s2 - coeff * s1 * s2;
+        2.0 * power.max(0.0).sqrt() / window.capacity() as f64
+    }
+    /// Returns true if the amplitude of the tone reaches the threshold.
+    pub fn detect(&self, window: &SlidingWindow<f64>) -> bool {
+        self.amplitude(window) >= self.threshold
+    }
+}
@@ -0,0 +1,187 @@
+//! A [`SlidingWindow`] with a segment tree on top, to aggregate any range of
+//! it in O(log N).
+use crate::aggregate::Aggregate;
+use crate::{panics, SlidingWindow};
+use std::ops::{Bound, RangeBounds};
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentTreeWindow;
+    use crate::aggregate::{Aggregate, Max, Sum};
+    struct Concat;
+    impl Aggregate<String> for Concat {
+        fn identity(&self) -> String {
+            String::new()
+        }
+        fn combine(&self, a: &String, b: &String) -> String {
+            format!("{a}{b}")
+        }
+    }
+    #[test]
+    fn matches_brute_force() {
+        let mut sum = SegmentTreeWindow::new(7, 0i64, Sum);
+        let mut max = SegmentTreeWindow::new(7, 0i64, Max(i64::MIN));
+        for i in 0..30i64 {
+            let x = (i * 37) % 11 - 5;
+            sum.push(x);
+            max.push(x);
+            let v = sum.window().to_vec();
+            for a in 0..=7 {
+                for b in a..=7 {
+                    assert_eq!(sum.query(a..b), v[a..b].iter().sum::<i64>());
+                    let m = v[a..b].iter().copied().max().unwrap_or(i64::MIN);
+                    assert_eq!(max.query(a..b), m);
+                }
+            }
+        }
+    }
+    #[test]
+    fn keeps_chronological_order() {
+        let mut st = SegmentTreeWindow::new(5, "-".to_string(), Concat);
+        for s in ["a", "b", "c", "d", "e", "f", "g"] {
+            st.push(s.to_string());
+        }
+        assert_eq!(*st.window(), ["g", "f", "e", "d", "c"].map(String::from));
+        assert_eq!(st.query(..), "cdefg");
+        assert_eq!(st.query(0..2), "fg");
+        assert_eq!(st.query(1..=3), "def");
+        assert_eq!(st.query(3..), "cd");
+        assert_eq!(st.aggregate(), "cdefg");
+    }
+    #[test]
+    fn single() {
+        let mut st = SegmentTreeWindow::new(0, 3, Sum);
+        assert_eq!(st.query(..), 3);
+        st.push(4);
+        assert_eq!(st.query(0..1), 4);
+        assert_eq!(st.query(1..1), 0);
+    }
+    #[test]
+    #[should_panic(expected = "range out of the window")]
+    fn out_of_range() {
+        let st = SegmentTreeWindow::new(3, 0, Sum);
+        st.query(1..4);
+    }
+}
+
+/// A [`SlidingWindow`] that answers [`Aggregate`] queries over any range of
+/// indexes, where, as always, `0` is the newest element. The aggregate is
+/// computed in chronological order.
+#[derive(Debug)]
+pub struct SegmentTreeWindow<T, A>
+where
+    T: Clone,
+    A: Aggregate<T>,
+{
+    window: SlidingWindow<T>,
+    op: A,
+    /// Inner nodes, the leaves are the elements of the window. Node `i` has as
+    /// children `2i` and `2i + 1`, and index `0` is unused.
+    tree: Vec<T>,
+}
+
+impl<T, A> SegmentTreeWindow<T, A>
+where
+    T: Clone,
+    A: Aggregate<T>,
+{
+    /// Same as [`SlidingWindow::new`], plus the operation to aggregate with.
+    pub fn new(max_items: usize, init: T, op: A) -> SegmentTreeWindow<T, A> {
+        let window = SlidingWindow::new(max_items, init);
+        let mut st = SegmentTreeWindow {
+            tree: vec![op.identity(); window.capacity],
+            window,
+            op,
+        };
+        for i in (1..st.window.capacity).rev() {
+            st.tree[i] = st.join(i);
+        }
+        st
+    }
+    /// Node `i` of the tree, which is a leaf from `capacity` onwards.
+    fn node(&self, i: usize) -> &T {
+        if i < self.window.capacity {
+            &self.tree[i]
+        } else {
+            &self.window.vec[i - self.window.capacity]
+        }
+    }
+    /// Aggregate of the children of node `i`. Higher positions of the vector
+    /// hold older elements, so the right child goes first.
+    fn join(&self, i: usize) -> T {
+        self.op.combine(self.node(2 * i + 1), self.node(2 * i))
+    }
+    /// Push an element to the window, updating the tree.
+    pub fn push(&mut self, a: T) {
+        let mut i = self.window.capacity - 1 - self.window.current_insert + self.window.capacity;
+        self.window.push(a);
+        i /= 2;
+        while i > 0 {
+            self.tree[i] = self.join(i);
+            i /= 2;
+        }
+    }
+    /// Push a slice, where the newest item is at index 0.
+    pub fn push_slice(&mut self, a: &[T]) {
+        a.iter().rev().for_each(|a| {
+            self.push(a.to_owned());
+        });
+    }
+    /// Aggregate of the positions `l..r` of the vector.
+    fn query_raw(&self, l: usize, r: usize) -> T {
+        let (mut l, mut r) = (l + self.window.capacity, r + self.window.capacity);
+        // newer and older ends of the range
+        let mut newer = self.op.identity();
+        let mut older = self.op.identity();
+        while l < r {
+            if l & 1 == 1 {
+                newer = self.op.combine(self.node(l), &newer);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                older = self.op.combine(&older, self.node(r));
+            }
+            l /= 2;
+            r /= 2;
+        }
+        self.op.combine(&older, &newer)
+    }
+    /// Returns the aggregate of the elements in `range`, using the same indexes
+    /// as [`SlidingWindow`]'s. An empty range returns the identity.
+    ///
+    /// # Panics
+    ///
+    /// If the range doesn't fit in the window.
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let cap = self.window.capacity;
+        let start = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&e) => e + 1,
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => cap,
+        };
+        assert!(start <= end && end <= cap, "{}", panics::OUT_OF_RANGE);
+        let first = (cap - self.window.current_insert + start) % cap;
+        let last = first + end - start;
+        if last <= cap {
+            self.query_raw(first, last)
+        } else {
+            // wraps around, the start of the vector holds the older part
+            let older = self.query_raw(0, last - cap);
+            self.op.combine(&older, &self.query_raw(first, cap))
+        }
+    }
+    /// Returns the aggregate of the whole window.
+    pub fn aggregate(&self) -> T {
+        self.query(..)
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<T> {
+        &self.window
+    }
+}
@@ -0,0 +1,504 @@
+//! Statistical process control charts, that keep their points in a
+//! [`SlidingWindow`] and check them against the Western Electric and Nelson
+//! rules on every push.
+//!
+//! The charts need the center and the standard deviation of the process when
+//! it's in control, which [`XbarRChart::from_window`] estimates from a window
+//! of samples.
+use crate::{panics, SlidingWindow};
+
+#[cfg(test)]
+mod tests {
+    use super::{CusumChart, EwmaChart, Rule, XbarRChart, NELSON, WESTERN_ELECTRIC};
+    use crate::test_noise as noisy;
+    use crate::SlidingWindow;
+    #[test]
+    fn rules() {
+        assert!(Rule::BeyondLimits.check(&[-3.5]));
+        assert!(Rule::TwoOfThree.check(&[2.5, 0.0, 2.1]));
+        assert!(!Rule::TwoOfThree.check(&[2.5, 0.0, -2.1]));
+        assert!(!Rule::TwoOfThree.check(&[0.0, 2.5, 2.1]));
+        assert!(Rule::FourOfFive.check(&[-1.5, -1.2, 0.0, -1.1, -2.0]));
+        assert!(Rule::EightOnOneSide.check(&[0.1; 8]));
+        assert!(!Rule::NineOnOneSide.check(&[0.1; 8]));
+        assert!(Rule::SixTrending.check(&[6.0, 5.0, 4.0, 3.0, 2.0, 1.0]));
+        let alternating: Vec<f64> = (0..14)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        assert!(Rule::FourteenAlternating.check(&alternating));
+        assert!(Rule::FifteenWithinOne.check(&alternating[..1].repeat(15)));
+        assert!(Rule::EightBeyondOne.check(&[1.5, -1.5, 1.5, -1.5, 1.5, -1.5, 1.5, -1.5]));
+        assert_eq!(WESTERN_ELECTRIC.len(), 4);
+        assert_eq!(NELSON.len(), 8);
+    }
+    #[test]
+    fn estimates_limits() {
+        let st: SlidingWindow<f64> = (0..100).map(|t| 10.0 + noisy(t)).collect();
+        let chart = XbarRChart::from_window(20, &st, 5, &WESTERN_ELECTRIC);
+        let mean = st.iter().sum::<f64>() / 100.0;
+        assert!((chart.mean_limits().center - mean).abs() < 1e-12);
+        let limits = chart.range_limits();
+        assert_eq!(limits.lower, 0.0);
+        assert!((limits.upper / limits.center - 2.114).abs() < 1e-3);
+        let individuals = XbarRChart::from_window(20, &st, 1, &NELSON);
+        assert!((individuals.sigma() - 0.23 / 1.128).abs() < 0.05);
+    }
+    #[test]
+    fn xbar_detects_shift() {
+        let mut chart = XbarRChart::new(20, 4, 10.0, 0.15, &WESTERN_ELECTRIC);
+        let mut fired = Vec::new();
+        for t in 0..200 {
+            let x = 10.0 + noisy(t) + if t < 100 { 0.0 } else { 0.2 };
+            if let Some(subgroup) = chart.push(x) {
+                assert!(subgroup.range.violations.is_empty());
+                if !subgroup.mean.violations.is_empty() {
+                    fired.push((t, subgroup.mean.violations));
+                }
+            }
+        }
+        assert!(fired[0].0 >= 100 && fired[0].0 < 120, "{fired:?}");
+        assert_eq!(chart.points().capacity(), 20);
+        assert!((chart.points()[0] - 10.2).abs() < 0.2);
+    }
+    #[test]
+    fn ewma_and_cusum() {
+        let mut ewma = EwmaChart::new(20, 10.0, 0.15, 0.2, 3.0);
+        let mut cusum = CusumChart::new(20, 10.0, 0.15, 0.5, 5.0);
+        let (mut e, mut c) = (None, None);
+        for t in 0..300 {
+            let x = 10.0 + noisy(t) + if t < 100 { 0.0 } else { -0.15 };
+            if !ewma.push(x).violations.is_empty() {
+                e.get_or_insert(t);
+            }
+            if !cusum.push(x).violations.is_empty() {
+                c.get_or_insert(t);
+            }
+        }
+        assert!((100..130).contains(&e.unwrap()));
+        assert!((100..130).contains(&c.unwrap()));
+        assert!(cusum.points()[0] < 0.0);
+        assert_eq!(ewma.limits().center, 10.0);
+    }
+    #[test]
+    #[should_panic(expected = "the window must hold at least two subgroups")]
+    fn short_window() {
+        let window = SlidingWindow::new(7, 10.0);
+        XbarRChart::from_window(20, &window, 4, &[]);
+    }
+}
+
+/// A rule on the standardized points of a chart, that signals that the
+/// process is out of control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// One point beyond the control limits, 3σ.
+    BeyondLimits,
+    /// Two out of three points beyond 2σ, on the same side.
+    TwoOfThree,
+    /// Four out of five points beyond 1σ, on the same side.
+    FourOfFive,
+    /// Eight points in a row on the same side of the center.
+    EightOnOneSide,
+    /// Nine points in a row on the same side of the center.
+    NineOnOneSide,
+    /// Six points in a row increasing or decreasing.
+    SixTrending,
+    /// Fourteen points in a row alternating up and down.
+    FourteenAlternating,
+    /// Fifteen points in a row within 1σ, on either side.
+    FifteenWithinOne,
+    /// Eight points in a row beyond 1σ, on either side.
+    EightBeyondOne,
+}
+
+/// The Western Electric rules.
+pub const WESTERN_ELECTRIC: [Rule; 4] = [
+    Rule::BeyondLimits,
+    Rule::TwoOfThree,
+    Rule::FourOfFive,
+    Rule::EightOnOneSide,
+];
+
+/// The Nelson rules, in their usual order.
+pub const NELSON: [Rule; 8] = [
+    Rule::BeyondLimits,
+    Rule::NineOnOneSide,
+    Rule::SixTrending,
+    Rule::FourteenAlternating,
+    Rule::TwoOfThree,
+    Rule::FourOfFive,
+    Rule::FifteenWithinOne,
+    Rule::EightBeyondOne,
+];
+
+/// Points needed by the longest rule.
+const MAX_POINTS: usize = 15;
+
+impl Rule {
+    /// Returns the amount of points the rule looks at.
+    pub fn points(self) -> usize {
+        match self {
+            Rule::BeyondLimits => 1,
+            Rule::TwoOfThree => 3,
+            Rule::FourOfFive => 5,
+            Rule::EightOnOneSide | Rule::EightBeyondOne => 8,
+            Rule::NineOnOneSide => 9,
+            Rule::SixTrending => 6,
+            Rule::FourteenAlternating => 14,
+            Rule::FifteenWithinOne => 15,
+        }
+    }
+    /// Checks the rule on the newest of the standardized points `z`, newest
+    /// first like a window. Patterns that don't include the newest point are
+    /// ignored, as they were reported before. Returns `false` if there aren't
+    /// enough points.
+    pub fn check(self, z: &[f64]) -> bool {
+        let n = self.points();
+        if z.len() < n {
+            return false;
+        }
+        let z = &z[..n];
+        // at least `k` of the points beyond `limit` on the side of the newest
+        let k_beyond = |k: usize, limit: f64| {
+            let side = z[0].signum();
+            z[0] * side > limit && z.iter().filter(|z| *z * side > limit).count() >= k
+        };
+        match self {
+            Rule::BeyondLimits => z[0].abs() > 3.0,
+            Rule::TwoOfThree => k_beyond(2, 2.0),
+            Rule::FourOfFive => k_beyond(4, 1.0),
+            Rule::EightOnOneSide | Rule::NineOnOneSide => {
+                z.iter().all(|z| *z > 0.0) || z.iter().all(|z| *z < 0.0)
+            }
+            Rule::SixTrending => {
+                z.windows(2).all(|w| w[0] > w[1]) || z.windows(2).all(|w| w[0] < w[1])
+            }
+            Rule::FourteenAlternating => z.windows(3).all(|w| (w[0] - w[1]) * (w[1] - w[2]) < 0.0),
+            Rule::FifteenWithinOne => z.iter().all(|z| z.abs() < 1.0),
+            Rule::EightBeyondOne => z.iter().all(|z| z.abs() > 1.0),
+        }
+    }
+}
+
+/// The center and control limits of a chart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Lower control limit.
+    pub lower: f64,
+    /// Center line.
+    pub center: f64,
+    /// Upper control limit.
+    pub upper: f64,
+}
+
+/// A point of a chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    /// The plotted value.
+    pub value: f64,
+    /// The rules that fired with this point, in the order they were given.
+    pub violations: Vec<Rule>,
+}
+
+/// A point of the X̄ chart and one of the R chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subgroup {
+    /// The mean of the subgroup, checked with all the rules.
+    pub mean: Point,
+    /// The range of the subgroup, only checked against the limits.
+    pub range: Point,
+}
+
+/// `d2` and `d3`, mean and standard deviation of the range of normal samples
+/// of unit deviation, for subgroups of 2 to 10.
+const RANGE_CONSTANTS: [(f64, f64); 9] = [
+    (1.128, 0.853),
+    (1.693, 0.888),
+    (2.059, 0.880),
+    (2.326, 0.864),
+    (2.534, 0.848),
+    (2.704, 0.833),
+    (2.847, 0.820),
+    (2.970, 0.808),
+    (3.078, 0.797),
+];
+
+/// Shewhart X̄ and R charts, of the means and ranges of consecutive subgroups
+/// of samples. With subgroups of one sample it is an individuals and moving
+/// range chart, where the range is the one between consecutive samples.
+#[derive(Debug, Clone)]
+pub struct XbarRChart {
+    samples: SlidingWindow<f64>,
+    /// Samples of the current subgroup.
+    filled: usize,
+    subgroup: usize,
+    center: f64,
+    sigma: f64,
+    rules: Vec<Rule>,
+    means: SlidingWindow<f64>,
+    /// Standardized means, for the rules.
+    z: SlidingWindow<f64>,
+    count: usize,
+}
+
+impl XbarRChart {
+    /// Creates a chart that keeps the last `max_items` points, of subgroups of
+    /// `subgroup` samples, of a process with mean `center` and standard
+    /// deviation `sigma`, checked with `rules`.
+    ///
+    /// # Panics
+    ///
+    /// If the subgroup isn't of 1 to 10 samples.
+    pub fn new(
+        max_items: usize,
+        subgroup: usize,
+        center: f64,
+        sigma: f64,
+        rules: &[Rule],
+    ) -> XbarRChart {
+        if !(1..=10).contains(&subgroup) {
+            panic!("{}", panics::SUBGROUP_SIZE);
+        }
+        XbarRChart {
+            samples: SlidingWindow::new(subgroup.max(2), center),
+            filled: 0,
+            subgroup,
+            center,
+            sigma,
+            rules: rules.to_vec(),
+            means: SlidingWindow::new(max_items, center),
+            z: SlidingWindow::new(MAX_POINTS, 0.0),
+            count: 0,
+        }
+    }
+    /// Same as [`XbarRChart::new`], with the center and deviation estimated
+    /// from the grand mean and the mean range of the subgroups of `window`, in
+    /// chronological order. The oldest samples that don't fill a subgroup are
+    /// ignored.
+    ///
+    /// # Panics
+    ///
+    /// If the subgroup isn't of 1 to 10 samples, or the window doesn't hold
+    /// at least two subgroups.
+    pub fn from_window(
+        max_items: usize,
+        window: &SlidingWindow<f64>,
+        subgroup: usize,
+        rules: &[Rule],
+    ) -> XbarRChart {
+        if !(1..=10).contains(&subgroup) {
+            panic!("{}", panics::SUBGROUP_SIZE);
+        }
+        if window.capacity() < 2 * subgroup {
+            panic!("{}", panics::TOO_FEW_SUBGROUPS);
+        }
+        let samples: Vec<f64> = window.iter().rev().copied().collect();
+        let samples = &samples[samples.len() % subgroup..];
+        let center = samples.iter().sum::<f64>() / samples.len() as f64;
+        let ranges: Vec<f64> = if subgroup == 1 {
+            samples.windows(2).map(|w| (w[1] - w[0]).abs()).collect()
+        } else {
+            samples.chunks(subgroup).map(range).collect()
+        };
+        let mean_range = ranges.iter().sum::<f64>() / ranges.len() as f64;
+        let (d2, _) = RANGE_CONSTANTS[subgroup.max(2) - 2];
+        XbarRChart::new(max_items, subgroup, center, mean_range / d2, rules)
+    }
+    /// Push a sample, returning the points of the charts when it completes a
+    /// subgroup.
+    pub fn push(&mut self, x: f64) -> Option<Subgroup> {
+        self.samples.push(x);
+        self.filled += 1;
+        if self.filled < self.samples.capacity() {
+            return None;
+        }
+        if self.subgroup > 1 {
+            self.filled = 0;
+        }
+        let mean = self.samples.iter().take(self.subgroup).sum::<f64>() / self.subgroup as f64;
+        let range = range(&self.samples.to_vec());
+        let limits = self.mean_limits();
+        self.means.push(mean);
+        self.z
+            .push(3.0 * (mean - limits.center) / (limits.upper - limits.center));
+        self.count += 1;
+        let z = &self.z.to_vec()[..self.count.min(MAX_POINTS)];
+        let violations = self.rules.iter().copied().filter(|r| r.check(z)).collect();
+        let limits = self.range_limits();
+        let range_violations = if range > limits.upper || range < limits.lower {
+            vec![Rule::BeyondLimits]
+        } else {
+            Vec::new()
+        };
+        Some(Subgroup {
+            mean: Point {
+                value: mean,
+                violations,
+            },
+            range: Point {
+                value: range,
+                violations: range_violations,
+            },
+        })
+    }
+    /// Returns the limits of the X̄ chart.
+    pub fn mean_limits(&self) -> Limits {
+        let width = 3.0 * self.sigma / (self.subgroup as f64).sqrt();
+        Limits {
+            lower: self.center - width,
+            center: self.center,
+            upper: self.center + width,
+        }
+    }
+    /// Returns the limits of the R chart.
+    pub fn range_limits(&self) -> Limits {
+        let (d2, d3) = RANGE_CONSTANTS[self.subgroup.max(2) - 2];
+        let center = d2 * self.sigma;
+        Limits {
+            lower: (center - 3.0 * d3 * self.sigma).max(0.0),
+            center,
+            upper: center + 3.0 * d3 * self.sigma,
+        }
+    }
+    /// Returns the standard deviation of the process.
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+    /// Returns the window of the means of the last subgroups.
+    pub fn points(&self) -> &SlidingWindow<f64> {
+        &self.means
+    }
+}
+
+fn range(x: &[f64]) -> f64 {
+    let max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = x.iter().copied().fold(f64::INFINITY, f64::min);
+    max - min
+}
+
+/// Exponentially weighted moving average chart, better than the X̄ chart at
+/// detecting small shifts. Its points only break [`Rule::BeyondLimits`], as
+/// they are correlated.
+#[derive(Debug, Clone)]
+pub struct EwmaChart {
+    center: f64,
+    sigma: f64,
+    lambda: f64,
+    width: f64,
+    average: f64,
+    /// `(1 - lambda)^(2 i)`, for the limits of the first points.
+    decay: f64,
+    points: SlidingWindow<f64>,
+}
+
+impl EwmaChart {
+    /// Creates a chart that keeps the last `max_items` points, for a process
+    /// with mean `center` and standard deviation `sigma`, with smoothing
+    /// `lambda`, usually from `0.05` to `0.25`, and limits `width` deviations
+    /// of the average away from the center, usually `3`.
+    pub fn new(max_items: usize, center: f64, sigma: f64, lambda: f64, width: f64) -> EwmaChart {
+        EwmaChart {
+            center,
+            sigma,
+            lambda,
+            width,
+            average: center,
+            decay: 1.0,
+            points: SlidingWindow::new(max_items, center),
+        }
+    }
+    /// Push a sample, returning the updated average.
+    pub fn push(&mut self, x: f64) -> Point {
+        self.average += self.lambda * (x - self.average);
+        self.decay *= (1.0 - self.lambda).powi(2);
+        self.points.push(self.average);
+        let limits = self.limits();
+        let violations = if self.average > limits.upper || self.average < limits.lower {
+            vec![Rule::BeyondLimits]
+        } else {
+            Vec::new()
+        };
+        Point {
+            value: self.average,
+            violations,
+        }
+    }
+    /// Returns the current limits, which widen during the first points.
+    pub fn limits(&self) -> Limits {
+        let var = self.lambda / (2.0 - self.lambda) * (1.0 - self.decay);
+        let width = self.width * self.sigma * var.sqrt();
+        Limits {
+            lower: self.center - width,
+            center: self.center,
+            upper: self.center + width,
+        }
+    }
+    /// Returns the window of the last averages.
+    pub fn points(&self) -> &SlidingWindow<f64> {
+        &self.points
+    }
+}
+
+/// Tabular CUSUM chart, with the allowance `k` and the decision interval `h`
+/// in deviations of the process. Unlike [`crate::changepoint::Cusum`], the sums
+/// aren't restarted after a signal, use `reset` once the cause is fixed.
+#[derive(Debug, Clone)]
+pub struct CusumChart {
+    center: f64,
+    k: f64,
+    h: f64,
+    high: f64,
+    low: f64,
+    points: SlidingWindow<f64>,
+}
+
+impl CusumChart {
+    /// Creates a chart that keeps the last `max_items` points, for a process
+    /// with mean `center` and standard deviation `sigma`. `k = 0.5` and
+    /// `h = 4` or `5` are the usual choices.
+    pub fn new(max_items: usize, center: f64, sigma: f64, k: f64, h: f64) -> CusumChart {
+        CusumChart {
+            center,
+            k: k * sigma,
+            h: h * sigma,
+            high: 0.0,
+            low: 0.0,
+            points: SlidingWindow::new(max_items, 0.0),
+        }
+    }
+    /// Push a sample, returning the upper sum, or minus the lower one if it is
+    /// bigger.
+    pub fn push(&mut self, x: f64) -> Point {
+        self.high = (self.high + x - self.center - self.k).max(0.0);
+        self.low = (self.low + self.center - x - self.k).max(0.0);
+        let value = if self.high >= self.low {
+            self.high
+        } else {
+            -self.low
+        };
+        self.points.push(value);
+        let violations = if value.abs() > self.h {
+            vec![Rule::BeyondLimits]
+        } else {
+            Vec::new()
+        };
+        Point { value, violations }
+    }
+    /// Returns the decision interval, around zero.
+    pub fn limits(&self) -> Limits {
+        Limits {
+            lower: -self.h,
+            center: 0.0,
+            upper: self.h,
+        }
+    }
+    /// Restarts the sums.
+    pub fn reset(&mut self) {
+        self.high = 0.0;
+        self.low = 0.0;
+    }
+    /// Returns the window of the last points.
+    pub fn points(&self) -> &SlidingWindow<f64> {
+        &self.points
+    }
+}
@@ -0,0 +1,386 @@
+//! Spectrum of the contents of a [`SlidingWindow`], with a built in FFT.
+//!
+//! The transform is taken over the chronological order of the window, oldest
+//! first, which is what any other library would expect. Powers of two use an
+//! iterative radix-2 FFT, and the rest of lengths use Bluestein's algorithm on
+//! top of it, so any capacity is O(N log N).
+use crate::{panics, SlidingWindow};
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[cfg(test)]
+mod tests {
+    use super::{Complex, Fft};
+    use crate::weighted::Taper;
+    use crate::SlidingWindow;
+    use std::f64::consts::PI;
+    fn dft(x: &[Complex]) -> Vec<Complex> {
+        let n = x.len();
+        (0..n)
+            .map(|k| {
+                x.iter()
+                    .enumerate()
+                    .fold(Complex::default(), |acc, (t, v)| {
+                        let ang = -2.0 * PI * (k * t) as f64 / n as f64;
+                        acc + *v * Complex::from_polar(1.0, ang)
+                    })
+            })
+            .collect()
+    }
+    fn check(n: usize) {
+        let x: Vec<Complex> = (0..n)
+            .map(|t| Complex::new((t as f64 * 0.7).sin() + 0.1 * t as f64, (t % 3) as f64))
+            .collect();
+        let mut y = x.clone();
+        Fft::new(n).forward(&mut y);
+        for (a, b) in y.iter().zip(dft(&x)) {
+            assert!((*a - b).norm() < 1e-9, "{n}: {a:?} != {b:?}");
+        }
+    }
+    #[test]
+    fn matches_dft() {
+        for n in [1, 2, 3, 5, 8, 12, 16, 17, 100] {
+            check(n);
+        }
+    }
+    #[test]
+    fn tone() {
+        let (fs, f) = (1024.0, 64.0);
+        let mut st = SlidingWindow::new(1024, 0.0);
+        for t in 0..1024 {
+            st.push(3.0 * (2.0 * PI * f * t as f64 / fs).sin());
+        }
+        let sp = st.spectrum(fs);
+        assert_eq!(sp.len(), 513);
+        let (freq, amp) = sp.peak().unwrap();
+        assert_eq!(freq, 64.0);
+        assert!((amp - 3.0).abs() < 1e-9);
+        // chronological order, so a sine starts with a phase of -pi/2
+        assert!((sp.phases()[64] + PI / 2.0).abs() < 1e-9);
+        assert_eq!(sp.frequencies()[512], 512.0);
+    }
+    #[test]
+    fn weighted() {
+        let mut st = SlidingWindow::new(6, 0.0);
+        st.push_slice(&[1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        let kernel = Taper::Hann.kernel(6);
+        let sp = st.spectrum_weighted(6.0, &kernel);
+        assert!((sp.bins()[0].re - kernel.iter().sum::<f64>()).abs() < 1e-12);
+        assert_eq!(sp.len(), 4);
+    }
+    #[test]
+    #[should_panic(expected = "the kernel must match the capacity of the window")]
+    fn short_kernel() {
+        SlidingWindow::new(8, 1.0).spectrum_weighted(1.0, &[1.0; 4]);
+    }
+}
+
+/// A complex number, as used by the FFT.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex {
+    /// Real part.
+    pub re: f64,
+    /// Imaginary part.
+    pub im: f64,
+}
+
+impl Complex {
+    /// Creates a complex from its real and imaginary parts.
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+    /// Creates a complex from its modulus and argument.
+    pub fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+    /// Returns the modulus.
+    pub fn norm(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+    /// Returns the argument, in `(-pi, pi]`.
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+    /// Returns the conjugate.
+    pub fn conj(&self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+    /// Multiplies by a real.
+    pub fn scale(&self, k: f64) -> Complex {
+        Complex::new(self.re * k, self.im * k)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, o: Complex) -> Complex {
+        Complex::new(self.re + o.re, self.im + o.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, o: Complex) -> Complex {
+        Complex::new(self.re - o.re, self.im - o.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, o: Complex) -> Complex {
+        Complex::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+}
+
+/// Precomputed data to transform a length.
+#[derive(Debug, Clone)]
+enum Plan {
+    /// `twiddles[k]` is `e^(-2 pi i k / n)`, for the first half.
+    Radix2 { twiddles: Vec<Complex> },
+    /// Convolution with a chirp, done with a power of two FFT.
+    Bluestein {
+        inner: Box<Fft>,
+        chirp: Vec<Complex>,
+        chirp_fft: Vec<Complex>,
+    },
+}
+
+/// A forward FFT for a fixed length, reusable between windows of the same
+/// capacity.
+#[derive(Debug, Clone)]
+pub struct Fft {
+    len: usize,
+    plan: Plan,
+}
+
+impl Fft {
+    /// Precomputes the FFT of length `len`, which is at least 1.
+    pub fn new(len: usize) -> Fft {
+        let len = len.max(1);
+        if len.is_power_of_two() {
+            let twiddles = (0..len / 2)
+                .map(|k| Complex::from_polar(1.0, -2.0 * PI * k as f64 / len as f64))
+                .collect();
+            return Fft {
+                len,
+                plan: Plan::Radix2 { twiddles },
+            };
+        }
+        let inner = Fft::new((2 * len - 1).next_power_of_two());
+        // e^(-pi i k^2 / n), k^2 reduced mod 2n to keep the precision
+        let chirp: Vec<Complex> = (0..len)
+            .map(|k| {
+                let k2 = (k * k) % (2 * len);
+                Complex::from_polar(1.0, -PI * k2 as f64 / len as f64)
+            })
+            .collect();
+        let mut chirp_fft = vec![Complex::default(); inner.len];
+        chirp_fft[0] = chirp[0].conj();
+        for k in 1..len {
+            chirp_fft[k] = chirp[k].conj();
+            chirp_fft[inner.len - k] = chirp[k].conj();
+        }
+        inner.forward(&mut chirp_fft);
+        Fft {
+            len,
+            plan: Plan::Bluestein {
+                inner: Box::new(inner),
+                chirp,
+                chirp_fft,
+            },
+        }
+    }
+    /// Returns the length it transforms.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Always false, as the length is at least 1.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+    /// Transforms `data` in place.
+    ///
+    /// # Panics
+    ///
+    /// If `data` isn't of the length of the FFT.
+    pub fn forward(&self, data: &mut [Complex]) {
+        assert_eq!(data.len(), self.len, "{}", panics::FFT_LENGTH);
+        match &self.plan {
+            Plan::Radix2 { twiddles } => radix2(data, twiddles),
+            Plan::Bluestein {
+                inner,
+                chirp,
+                chirp_fft,
+            } => {
+                let mut buf = vec![Complex::default(); inner.len];
+                buf.iter_mut()
+                    .zip(data.iter().zip(chirp))
+                    .for_each(|(b, (x, w))| *b = *x * *w);
+                inner.forward(&mut buf);
+                // inverse transform as the conjugate of the forward one
+                buf.iter_mut()
+                    .zip(chirp_fft)
+                    .for_each(|(b, c)| *b = (*b * *c).conj());
+                inner.forward(&mut buf);
+                let scale = 1.0 / inner.len as f64;
+                data.iter_mut()
+                    .zip(buf.iter().zip(chirp))
+                    .for_each(|(x, (b, w))| *x = b.conj().scale(scale) * *w);
+            }
+        }
+    }
+    /// Returns the spectrum of the window, sampled at `sample_rate`.
+    ///
+    /// # Panics
+    ///
+    /// If the capacity of the window isn't the length of the FFT.
+    pub fn spectrum(&self, window: &SlidingWindow<f64>, sample_rate: f64) -> Spectrum {
+        let data: Vec<f64> = window.iter().rev().copied().collect();
+        self.spectrum_of(data, sample_rate)
+    }
+    /// Same as `spectrum`, but weighting the window with a kernel aligned with
+    /// it, newest first, like the ones in [`crate::weighted`].
+    ///
+    /// # Panics
+    ///
+    /// If the kernel isn't as long as the window, or the capacity of the
+    /// window isn't the length of the FFT.
+    pub fn spectrum_weighted(
+        &self,
+        window: &SlidingWindow<f64>,
+        sample_rate: f64,
+        kernel: &[f64],
+    ) -> Spectrum {
+        assert_eq!(kernel.len(), window.capacity, "{}", panics::KERNEL_LENGTH);
+        let mut data: Vec<f64> = window.weighted_iter(kernel).collect();
+        data.reverse();
+        self.spectrum_of(data, sample_rate)
+    }
+    /// Spectrum of chronologically ordered data.
+    fn spectrum_of(&self, data: Vec<f64>, sample_rate: f64) -> Spectrum {
+        let mut buf: Vec<Complex> = data.into_iter().map(|x| Complex::new(x, 0.0)).collect();
+        self.forward(&mut buf);
+        buf.truncate(self.len / 2 + 1);
+        Spectrum {
+            bins: buf,
+            len: self.len,
+            sample_rate,
+        }
+    }
+}
+
+/// Iterative in place radix-2 FFT.
+fn radix2(data: &mut [Complex], twiddles: &[Complex]) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let step = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let u = data[start + k];
+                let v = data[start + k + len / 2] * twiddles[k * step];
+                data[start + k] = u + v;
+                data[start + k + len / 2] = u - v;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// The one sided spectrum of a real window, from DC up to the Nyquist
+/// frequency.
+#[derive(Debug, Clone)]
+pub struct Spectrum {
+    bins: Vec<Complex>,
+    len: usize,
+    sample_rate: f64,
+}
+
+impl Spectrum {
+    /// Returns the amount of bins, `N / 2 + 1`.
+    pub fn len(&self) -> usize {
+        self.bins.len()
+    }
+    /// Always false, there is at least the DC bin.
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+    /// Returns the raw bins of the transform.
+    pub fn bins(&self) -> &[Complex] {
+        &self.bins
+    }
+    /// Returns the frequency of bin `k`.
+    pub fn frequency(&self, k: usize) -> f64 {
+        k as f64 * self.sample_rate / self.len as f64
+    }
+    /// Returns the frequency of every bin.
+    pub fn frequencies(&self) -> Vec<f64> {
+        (0..self.len()).map(|k| self.frequency(k)).collect()
+    }
+    /// Returns the modulus of every bin.
+    pub fn magnitudes(&self) -> Vec<f64> {
+        self.bins.iter().map(Complex::norm).collect()
+    }
+    /// Returns the magnitudes scaled so that a sinusoid that falls on a bin
+    /// has its amplitude, and DC its mean.
+    pub fn amplitudes(&self) -> Vec<f64> {
+        let n = self.len as f64;
+        self.bins
+            .iter()
+            .enumerate()
+            .map(|(k, b)| {
+                if k == 0 || 2 * k == self.len {
+                    b.norm() / n
+                } else {
+                    2.0 * b.norm() / n
+                }
+            })
+            .collect()
+    }
+    /// Returns the argument of every bin, in radians.
+    pub fn phases(&self) -> Vec<f64> {
+        self.bins.iter().map(Complex::arg).collect()
+    }
+    /// Returns the frequency and amplitude of the highest bin other than DC,
+    /// or `None` if there is only DC.
+    pub fn peak(&self) -> Option<(f64, f64)> {
+        self.amplitudes()
+            .into_iter()
+            .enumerate()
+            .skip(1)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(k, a)| (self.frequency(k), a))
+    }
+}
+
+impl SlidingWindow<f64> {
+    /// Returns the spectrum of the window, sampled at `sample_rate`. If you
+    /// do this often, keep an [`Fft`] and use its `spectrum` instead.
+    pub fn spectrum(&self, sample_rate: f64) -> Spectrum {
+        Fft::new(self.capacity).spectrum(self, sample_rate)
+    }
+    /// Same as `spectrum`, but weighting the window with a kernel, newest
+    /// first, like the ones in [`crate::weighted`].
+    ///
+    /// # Panics
+    ///
+    /// If the kernel isn't as long as the window.
+    pub fn spectrum_weighted(&self, sample_rate: f64, kernel: &[f64]) -> Spectrum {
+        Fft::new(self.capacity).spectrum_weighted(self, sample_rate, kernel)
+    }
+}
@@ -0,0 +1,147 @@
+//! Adaptors that buffer an asynchronous [`Stream`] into a [`SlidingWindow`].
+//! Only available with the `async` feature.
+use crate::hopping::HoppingWindow;
+use crate::SlidingWindow;
+use futures_core::{ready, Stream};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(test)]
+mod tests {
+    use super::SlidingStreamExt;
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+    #[test]
+    fn windows_every_item() {
+        let st = stream::iter(1..=4).windows(3, 0, 1);
+        let out: Vec<Vec<i32>> = block_on(st.collect());
+        assert_eq!(
+            out,
+            [vec![1, 0, 0], vec![2, 1, 0], vec![3, 2, 1], vec![4, 3, 2]]
+        );
+    }
+    #[test]
+    fn windows_hop() {
+        let st = stream::iter(1..=7).windows(3, 0, 3);
+        let out: Vec<Vec<i32>> = block_on(st.collect());
+        assert_eq!(out, [vec![3, 2, 1], vec![6, 5, 4]]);
+    }
+    #[test]
+    fn windowed_reduction() {
+        let st = stream::iter([1.0, 2.0, 3.0, 4.0])
+            .windowed(2, 0.0, 2, |w| w.iter().sum::<f64>() / w.capacity() as f64);
+        let out: Vec<f64> = block_on(st.collect());
+        assert_eq!(out, [1.5, 3.5]);
+    }
+    #[test]
+    fn zero_hop() {
+        let st = stream::iter(1..=2).windowed(2, 0, 0, |w| w[0]);
+        let out: Vec<i32> = block_on(st.collect());
+        assert_eq!(out, [1, 2]);
+    }
+}
+
+/// Function used by [`SlidingStreamExt::windows`] to copy out the window.
+pub type Snapshot<T> = fn(&SlidingWindow<T>) -> Vec<T>;
+
+/// Extension methods for any [`Stream`] whose items can be stored in a
+/// [`SlidingWindow`].
+pub trait SlidingStreamExt: Stream + Sized
+where
+    Self::Item: Clone,
+{
+    /// Pushes every item into a window of `capacity` elements initialized to
+    /// `init`, and every `hop` items yields a snapshot of the window, newest
+    /// first, as [`SlidingWindow::to_vec`] would.
+    fn windows(
+        self,
+        capacity: usize,
+        init: Self::Item,
+        hop: usize,
+    ) -> Windowed<Self, Snapshot<Self::Item>> {
+        self.windowed(capacity, init, hop, SlidingWindow::to_vec)
+    }
+    /// Same as `windows`, but instead of a snapshot it yields the result of
+    /// calling `f` on the window, so reductions don't have to copy it.
+    fn windowed<F, R>(
+        self,
+        capacity: usize,
+        init: Self::Item,
+        hop: usize,
+        f: F,
+    ) -> Windowed<Self, F>
+    where
+        F: FnMut(&SlidingWindow<Self::Item>) -> R,
+    {
+        Windowed {
+            stream: self,
+            window: HoppingWindow::new(capacity, init, hop),
+            f,
+        }
+    }
+}
+
+impl<S> SlidingStreamExt for S
+where
+    S: Stream,
+    S::Item: Clone,
+{
+}
+
+/// Stream returned by [`SlidingStreamExt::windowed`] and
+/// [`SlidingStreamExt::windows`].
+///
+/// The inner stream has to be [`Unpin`], use `Box::pin` on it if it isn't.
+#[derive(Debug)]
+pub struct Windowed<S, F>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    stream: S,
+    window: HoppingWindow<S::Item>,
+    f: F,
+}
+
+impl<S, F> Windowed<S, F>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    /// Returns the window as it is after the last item pulled from the stream.
+    pub fn window(&self) -> &SlidingWindow<S::Item> {
+        self.window.window()
+    }
+}
+
+// The window is never pinned, so only the stream and the closure matter
+impl<S, F> Unpin for Windowed<S, F>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+    F: Unpin,
+{
+}
+
+impl<S, F, R> Stream for Windowed<S, F>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+    F: FnMut(&SlidingWindow<S::Item>) -> R + Unpin,
+{
+    type Item = R;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        let this = self.get_mut();
+        while let Some(item) = ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+            if let Some(window) = this.window.push(item) {
+                return Poll::Ready(Some((this.f)(window)));
+            }
+        }
+        Poll::Ready(None)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.stream.size_hint();
+        let emit = |n: usize| (n + self.window.pending()) / self.window.hop();
+        (emit(low), high.map(emit))
+    }
+}
@@ -0,0 +1,210 @@
+//! Capture of the samples around an event, like the trigger of an
+//! oscilloscope: a [`SlidingWindow`] keeps the samples before it and, once it
+//! fires, the following ones are collected.
+use crate::SlidingWindow;
+use std::fmt;
+
+#[cfg(test)]
+mod tests {
+    use super::{Capture, Edge, Trigger, TriggerCapture};
+    #[test]
+    fn level() {
+        let mut tc = TriggerCapture::new(3, 2, 0, Trigger::level(5, Edge::Rising));
+        let mut captures = Vec::new();
+        for x in [1, 2, 6, 7, 3, 4, 1, 2, 3, 5, 9, 9, 9, 1, 8] {
+            if let Some(capture) = tc.push(x) {
+                captures.push(capture);
+            }
+        }
+        assert_eq!(
+            captures,
+            [
+                Capture {
+                    samples: vec![1, 2, 6, 7, 3],
+                    trigger: 2,
+                },
+                Capture {
+                    samples: vec![1, 2, 3, 5, 9, 9],
+                    trigger: 3,
+                }
+            ]
+        );
+        assert!(tc.is_capturing());
+        assert_eq!(tc.pretrigger()[0], 8);
+    }
+    #[test]
+    fn falling_and_predicate() {
+        let mut tc = TriggerCapture::new(2, 1, 0.0, Trigger::level(0.0, Edge::Falling));
+        assert!(tc.push(-1.0).is_none());
+        assert!(tc.push(1.0).is_none());
+        assert!(tc.push(-1.0).is_none());
+        let capture = tc.push(2.0).unwrap();
+        assert_eq!(capture.samples, [-1.0, 1.0, -1.0, 2.0]);
+        assert_eq!(capture.trigger, 2);
+        let jump = 10;
+        let mut tc =
+            TriggerCapture::new(2, 0, 0, Trigger::predicate(move |prev, x| x - prev > jump));
+        assert!(tc.push(3).is_none());
+        let capture = tc.push(20).unwrap();
+        assert_eq!((capture.samples, capture.trigger), (vec![3, 20], 1));
+        tc.disarm();
+        assert!(tc.push(40).is_none());
+        tc.arm();
+        assert!(tc.push(60).is_some());
+    }
+}
+
+/// Which crossings of the level fire the trigger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// From below the level to it or above.
+    Rising,
+    /// From above the level to it or below.
+    Falling,
+    /// Either of them.
+    Either,
+}
+
+/// A function of the previous sample and the new one.
+pub type PredicateFn<T> = Box<dyn Fn(&T, &T) -> bool + Send>;
+
+/// The condition that starts a capture.
+pub enum Trigger<T> {
+    /// The samples cross a level.
+    Level {
+        /// The level.
+        level: T,
+        /// Which crossings fire.
+        edge: Edge,
+    },
+    /// A function of the previous sample and the new one returns `true`.
+    Predicate(PredicateFn<T>),
+}
+
+impl<T> Trigger<T> {
+    /// Creates a level trigger.
+    pub fn level(level: T, edge: Edge) -> Trigger<T> {
+        Trigger::Level { level, edge }
+    }
+    /// Creates a trigger from a function of the previous sample and the new
+    /// one, which can capture its environment.
+    pub fn predicate<F: Fn(&T, &T) -> bool + Send + 'static>(f: F) -> Trigger<T> {
+        Trigger::Predicate(Box::new(f))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Trigger<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Level { level, edge } => f
+                .debug_struct("Level")
+                .field("level", level)
+                .field("edge", edge)
+                .finish(),
+            Trigger::Predicate(_) => f.write_str("Predicate(..)"),
+        }
+    }
+}
+
+impl<T: PartialOrd> Trigger<T> {
+    /// Returns whether going from `previous` to `current` fires the trigger.
+    pub fn fires(&self, previous: &T, current: &T) -> bool {
+        match self {
+            Trigger::Level { level, edge } => {
+                let rising = previous < level && current >= level;
+                let falling = previous > level && current <= level;
+                match edge {
+                    Edge::Rising => rising,
+                    Edge::Falling => falling,
+                    Edge::Either => rising || falling,
+                }
+            }
+            Trigger::Predicate(f) => f(previous, current),
+        }
+    }
+}
+
+/// The samples around a trigger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture<T> {
+    /// The samples, in chronological order, from the oldest.
+    pub samples: Vec<T>,
+    /// Index of the sample that fired the trigger.
+    pub trigger: usize,
+}
+
+/// Keeps the samples before a trigger and collects the ones after it.
+///
+/// Triggers during a capture are ignored. Once a capture is complete the
+/// trigger is armed again, with the samples before it already in the window.
+#[derive(Debug)]
+pub struct TriggerCapture<T: Clone> {
+    pre: SlidingWindow<T>,
+    /// Samples pushed to `pre`, up to its capacity.
+    filled: usize,
+    post: usize,
+    trigger: Trigger<T>,
+    armed: bool,
+    /// The capture in progress.
+    capture: Option<Capture<T>>,
+}
+
+impl<T: Clone + PartialOrd> TriggerCapture<T> {
+    /// Creates a capture of `pre` samples, at least 1, before the trigger and
+    /// `post` after it. `init` fills the window, but only the samples that are
+    /// pushed end in a capture.
+    pub fn new(pre: usize, post: usize, init: T, trigger: Trigger<T>) -> TriggerCapture<T> {
+        TriggerCapture {
+            pre: SlidingWindow::new(pre, init),
+            filled: 0,
+            post,
+            trigger,
+            armed: true,
+            capture: None,
+        }
+    }
+    /// Push a sample, returning the capture when it is complete.
+    pub fn push(&mut self, x: T) -> Option<Capture<T>> {
+        if let Some(capture) = &mut self.capture {
+            capture.samples.push(x.clone());
+        } else if self.armed && self.filled > 0 && self.trigger.fires(&self.pre[0], &x) {
+            let mut samples: Vec<T> = self.pre.iter().take(self.filled).cloned().collect();
+            samples.reverse();
+            let trigger = samples.len();
+            samples.push(x.clone());
+            self.capture = Some(Capture { samples, trigger });
+        }
+        self.pre.push(x);
+        self.filled = (self.filled + 1).min(self.pre.capacity());
+        let complete = self
+            .capture
+            .as_ref()
+            .is_some_and(|c| c.samples.len() > c.trigger + self.post);
+        if complete {
+            self.capture.take()
+        } else {
+            None
+        }
+    }
+    /// Arms the trigger, which is armed at creation.
+    pub fn arm(&mut self) {
+        self.armed = true;
+    }
+    /// Disarms the trigger, so that it doesn't start new captures. A capture
+    /// in progress still completes.
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+    /// Returns whether the trigger is armed.
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+    /// Returns whether a capture is in progress.
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+    /// Returns the window of the samples before the trigger.
+    pub fn pretrigger(&self) -> &SlidingWindow<T> {
+        &self.pre
+    }
+}
@@ -0,0 +1,239 @@
+//! Tapers and weighted views of a [`SlidingWindow`], for spectral analysis and
+//! weighted moving averages.
+//!
+//! Kernels are aligned with [`SlidingWindow::iter`], so their index `0` weights
+//! the newest element. All the tapers are the symmetric version, of length
+//! equal to the capacity.
+use crate::SlidingWindow;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+#[cfg(test)]
+mod tests {
+    use super::{KernelCache, Taper, WeightedWindow};
+    use crate::test_close as close;
+    use crate::SlidingWindow;
+    #[test]
+    fn tapers() {
+        assert!(close(&Taper::Rectangular.kernel(3), &[1.0; 3]));
+        assert!(close(&Taper::Hann.kernel(5), &[0.0, 0.5, 1.0, 0.5, 0.0]));
+        assert!(close(&Taper::Hamming.kernel(3), &[0.08, 1.0, 0.08]));
+        assert!(close(&Taper::Blackman.kernel(3), &[0.0, 1.0, 0.0]));
+        assert!(close(&Taper::Kaiser(0.0).kernel(4), &[1.0; 4]));
+        let k = Taper::Kaiser(5.0).kernel(7);
+        assert!((k[3] - 1.0).abs() < 1e-12 && (k[0] - 1.0 / 27.239871823604).abs() < 1e-9);
+        let g = Taper::Gaussian(0.5).kernel(5);
+        assert!(close(
+            &g,
+            &[
+                (-2.0f64).exp(),
+                (-0.5f64).exp(),
+                1.0,
+                (-0.5f64).exp(),
+                (-2.0f64).exp()
+            ]
+        ));
+        assert!(close(&Taper::Hann.kernel(1), &[1.0]));
+    }
+    #[test]
+    fn custom_is_newest_first() {
+        let linear = Taper::Custom(|n, len| (len - n) as f64);
+        assert!(close(&linear.kernel(3), &[3.0, 2.0, 1.0]));
+        let mut ww = WeightedWindow::new(3, 0.0, linear);
+        ww.push_slice(&[3.0, 2.0, 1.0]);
+        let w: Vec<f64> = ww.weighted_iter().collect();
+        assert!(close(&w, &[9.0, 4.0, 1.0]));
+        assert!((ww.weighted_mean() - 14.0 / 6.0).abs() < 1e-12);
+        assert!(WeightedWindow::with_kernel(&[1.0, 2.0], vec![1.0]).is_none());
+        let ww = WeightedWindow::with_kernel(&[1.0, 2.0], vec![1.0, 3.0]).unwrap();
+        assert_eq!(ww.weighted_mean(), 7.0 / 4.0);
+    }
+    #[test]
+    fn weighted_iter() {
+        let mut st = SlidingWindow::new(3, 1.0);
+        st.push(4.0);
+        let w: Vec<f64> = st.weighted_iter(&[0.5, 1.0, 2.0]).collect();
+        assert_eq!(w, [2.0, 1.0, 2.0]);
+    }
+    #[test]
+    fn cache() {
+        let mut cache = KernelCache::new(Taper::Hann);
+        assert!(close(cache.get(5), &[0.0, 0.5, 1.0, 0.5, 0.0]));
+        assert_eq!(cache.get(8).len(), 8);
+        assert_eq!(cache.len(), 2);
+        cache.get(5);
+        assert_eq!(cache.len(), 2);
+    }
+}
+
+/// The shape of the weights of a window.
+#[derive(Debug, Clone, Copy)]
+pub enum Taper {
+    /// All ones, the same as not weighting.
+    Rectangular,
+    /// Raised cosine that reaches zero at both ends.
+    Hann,
+    /// Raised cosine that doesn't reach zero, with lower first side lobe.
+    Hamming,
+    /// Three term cosine, with very low side lobes.
+    Blackman,
+    /// Kaiser–Bessel window, with the given `beta`. `0` is rectangular, and
+    /// around `8.6` resembles a Blackman.
+    Kaiser(f64),
+    /// Gaussian window, with the given standard deviation, relative to half the
+    /// length, so `0.5` reaches `e^-2` at the ends.
+    Gaussian(f64),
+    /// A function that takes the index of the element, newest first, and the
+    /// length of the window, and returns its weight.
+    Custom(fn(usize, usize) -> f64),
+}
+
+impl Taper {
+    /// Generates the weights for a window of length `len`.
+    pub fn kernel(&self, len: usize) -> Vec<f64> {
+        if let Taper::Custom(f) = self {
+            return (0..len).map(|n| f(n, len)).collect();
+        }
+        if len == 1 {
+            return vec![1.0];
+        }
+        let m = (len - 1) as f64;
+        (0..len)
+            .map(|n| {
+                let n = n as f64;
+                let x = 2.0 * PI * n / m;
+                match self {
+                    Taper::Rectangular => 1.0,
+                    Taper::Hann => 0.5 - 0.5 * x.cos(),
+                    Taper::Hamming => 0.54 - 0.46 * x.cos(),
+                    Taper::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                    Taper::Kaiser(beta) => {
+                        let r = 2.0 * n / m - 1.0;
+                        bessel_i0(beta * (1.0 - r * r).max(0.0).sqrt()) / bessel_i0(*beta)
+                    }
+                    Taper::Gaussian(sigma) => {
+                        let r = (n - m / 2.0) / (sigma * m / 2.0);
+                        (-0.5 * r * r).exp()
+                    }
+                    Taper::Custom(_) => unreachable!(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Modified Bessel function of the first kind and order zero, by its series.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-16 {
+        term *= (x / (2.0 * k)).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Generates the kernels of a [`Taper`] once per length, to share them between
+/// windows.
+#[derive(Debug, Clone)]
+pub struct KernelCache {
+    taper: Taper,
+    kernels: HashMap<usize, Vec<f64>>,
+}
+
+impl KernelCache {
+    /// Creates an empty cache for `taper`.
+    pub fn new(taper: Taper) -> KernelCache {
+        KernelCache {
+            taper,
+            kernels: HashMap::new(),
+        }
+    }
+    /// Returns the kernel of length `len`, generating it the first time.
+    pub fn get(&mut self, len: usize) -> &[f64] {
+        let taper = self.taper;
+        self.kernels.entry(len).or_insert_with(|| taper.kernel(len))
+    }
+    /// Returns the amount of kernels generated.
+    pub fn len(&self) -> usize {
+        self.kernels.len()
+    }
+    /// Returns true if no kernel has been generated yet.
+    pub fn is_empty(&self) -> bool {
+        self.kernels.is_empty()
+    }
+}
+
+impl SlidingWindow<f64> {
+    /// Returns an iterator over the elements, newest first, multiplied by the
+    /// weights in `kernel`. If the kernel is shorter than the window, the
+    /// oldest elements are left out.
+    pub fn weighted_iter<'a>(&'a self, kernel: &'a [f64]) -> impl Iterator<Item = f64> + 'a {
+        self.iter().zip(kernel).map(|(x, w)| x * w)
+    }
+}
+
+/// A [`SlidingWindow`] of `f64` with the kernel of a [`Taper`] generated for
+/// its capacity.
+#[derive(Debug, Clone)]
+pub struct WeightedWindow {
+    window: SlidingWindow<f64>,
+    kernel: Vec<f64>,
+    kernel_sum: f64,
+}
+
+impl WeightedWindow {
+    /// Same as [`SlidingWindow::new`], plus the taper to weight with.
+    pub fn new(max_items: usize, init: f64, taper: Taper) -> WeightedWindow {
+        let window = SlidingWindow::new(max_items, init);
+        let kernel = taper.kernel(window.capacity());
+        WeightedWindow {
+            window,
+            kernel_sum: kernel.iter().sum(),
+            kernel,
+        }
+    }
+    /// Creates a weighted window from its contents and kernel, both newest
+    /// first. Returns `None` if the lengths are different.
+    ///
+    /// # Panics
+    ///
+    /// Same as `From<&[T]>` for [`SlidingWindow`], if they are empty.
+    pub fn with_kernel(contents: &[f64], kernel: Vec<f64>) -> Option<WeightedWindow> {
+        if contents.len() != kernel.len() {
+            return None;
+        }
+        Some(WeightedWindow {
+            window: SlidingWindow::from(contents),
+            kernel_sum: kernel.iter().sum(),
+            kernel,
+        })
+    }
+    /// Push an element to the window, forgetting the oldest.
+    pub fn push(&mut self, a: f64) {
+        self.window.push(a);
+    }
+    /// Push a slice, where the newest item is at index 0.
+    pub fn push_slice(&mut self, a: &[f64]) {
+        self.window.push_slice(a);
+    }
+    /// Returns the elements, newest first, multiplied by their weights.
+    pub fn weighted_iter(&self) -> impl Iterator<Item = f64> + '_ {
+        self.window.weighted_iter(&self.kernel)
+    }
+    /// Returns the weighted mean, the weighted sum divided by the sum of the
+    /// weights.
+    pub fn weighted_mean(&self) -> f64 {
+        self.weighted_iter().sum::<f64>() / self.kernel_sum
+    }
+    /// Returns the weights, newest first.
+    pub fn kernel(&self) -> &[f64] {
+        &self.kernel
+    }
+    /// Returns the underlying window.
+    pub fn window(&self) -> &SlidingWindow<f64> {
+        &self.window
+    }
+}
@@ -0,0 +1,71 @@
+//! The panic hook of the flight recorder is global, so it is tested in its own
+//! binary, away from the panics that other tests expect.
+use sliding_window_alt::recorder::FlightRecorder;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn dumps_on_panic() {
+    let fr = FlightRecorder::new(2);
+    let out = Shared::default();
+    fr.install_panic_hook_to(out.clone());
+    let recorder = fr.clone();
+    let result = std::thread::spawn(move || {
+        recorder.record(1.5);
+        recorder.record(2.5);
+        panic!("controller failed");
+    })
+    .join();
+    assert!(result.is_err());
+    let out = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+    assert!(out.contains("flight recorder, last 2 of 2 records:\n#0 1.5\n#1 2.5\n"));
+    fr.record(3.5);
+    assert_eq!(fr.records(), [(1, 2.5), (2, 3.5)]);
+}
+
+/// A record that, when evicted, holds the records for a while.
+#[derive(Clone, Debug)]
+struct Slow(u32);
+
+static EVICTING: AtomicBool = AtomicBool::new(false);
+
+impl Drop for Slow {
+    fn drop(&mut self) {
+        if self.0 == 0 {
+            EVICTING.store(true, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+#[test]
+fn dumps_while_another_thread_records() {
+    let fr = FlightRecorder::new(1);
+    fr.record(Slow(0));
+    let out = Shared::default();
+    fr.install_panic_hook_to(out.clone());
+    let recorder = fr.clone();
+    let writer = std::thread::spawn(move || recorder.record(Slow(1)));
+    while !EVICTING.load(Ordering::SeqCst) {
+        std::thread::yield_now();
+    }
+    let result = std::thread::spawn(|| panic!("controller failed")).join();
+    assert!(result.is_err());
+    writer.join().unwrap();
+    let out = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+    assert!(out.contains("flight recorder, last 1 of 2 records:\n#1 Slow(1)\n"));
+}